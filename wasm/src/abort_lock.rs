@@ -14,6 +14,12 @@ impl AbortLock {
     *(self.0.write().unwrap()) = true;
   }
 
+  /// Clears the abort, so the same lock can guard the next solve. Must not be called
+  /// while a solve using the lock is still running, it would miss the abort.
+  pub fn reset(&self) {
+    *(self.0.write().unwrap()) = false;
+  }
+
   pub fn is_locked(&self) -> bool {
     !*(self.0.read().unwrap())
   }
@@ -23,6 +29,20 @@ impl AbortLock {
   }
 }
 
+impl AbortLock {
+  /// Lock which is never aborted, same as `prepare`
+  pub fn never() -> AbortLock {
+    AbortLock::prepare()
+  }
+
+  /// Lock which is aborted from the start
+  pub fn aborted() -> AbortLock {
+    let lock = AbortLock::prepare();
+    lock.abort();
+    lock
+  }
+}
+
 #[cfg(test)]
 mod test {
 
@@ -53,6 +73,23 @@ mod test {
     assert!(start.elapsed() <= Duration::from_millis(100));
   }
 
+  #[test]
+  fn explicit_constructors() {
+    assert!(!AbortLock::never().is_aborted());
+    assert!(AbortLock::aborted().is_aborted());
+    assert!(!AbortLock::aborted().is_locked());
+  }
+
+  #[test]
+  fn reset_after_abort() {
+    let lock = AbortLock::aborted();
+    lock.reset();
+    assert!(lock.is_locked());
+    assert!(!lock.is_aborted());
+    lock.abort();
+    assert!(lock.is_aborted());
+  }
+
   #[test]
   fn do_not_abort_by_itself() {
     let lock = Box::leak(Box::new(AbortLock::prepare()));
@@ -0,0 +1,70 @@
+use crate::abort_lock::AbortLock;
+use crate::solver::solution;
+use crate::sudoku::Sudoku;
+use crate::techniques::solve_logically;
+
+/// Way of completing a board which callers can pick without knowing how it works
+pub trait SolveBackend {
+  /// Completed board, `None` when this backend can't finish it or the lock got aborted
+  fn solve(&self, sudoku: &Sudoku, lock: &AbortLock) -> Option<Sudoku>;
+}
+
+/// Search of `solution`, finds a completion of every solvable board
+#[derive(Clone, Copy, Default, Debug)]
+pub struct BruteForceSolver;
+
+/// Techniques of `solve_logically`, gives up on boards which need guessing
+#[derive(Clone, Copy, Default, Debug)]
+pub struct LogicSolver;
+
+impl SolveBackend for BruteForceSolver {
+  fn solve(&self, sudoku: &Sudoku, lock: &AbortLock) -> Option<Sudoku> {
+    solution(sudoku.clone(), lock)
+  }
+}
+
+impl SolveBackend for LogicSolver {
+  fn solve(&self, sudoku: &Sudoku, lock: &AbortLock) -> Option<Sudoku> {
+    match solve_logically(sudoku.clone(), lock) {
+      (solved, true) => Some(solved),
+      _ => None
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::backend::*;
+
+  #[test]
+  fn backends_agree_on_medium_sudoku() {
+    let data 
+      = "".to_string()
+      + "  8" + "  3" + "461"
+      + "2 6" + " 84" + "   "
+      + "3  " + "  7" + " 9 "
+
+      + " 3 " + "75 " + "68 "
+      + " 87" + " 1 " + "   "
+      + " 5 " + " 4 " + "13 "
+
+      + "  9" + "27 " + "31 "
+      + "   " + "   " + "   "
+      + "763" + "4 1" + "8 2";
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    let lock = AbortLock::prepare();
+    let backends: Vec<Box<dyn SolveBackend>> = vec![Box::new(BruteForceSolver), Box::new(LogicSolver)];
+
+    let solutions: Vec<Option<Sudoku>> = backends.iter().map(|backend| backend.solve(&sudoku, &lock)).collect();
+    assert!(solutions[0].as_ref().is_some_and(|s| s.is_solved()));
+    assert_eq!(solutions[0], solutions[1]);
+  }
+
+  #[test]
+  fn logic_solver_gives_up_without_guessing() {
+    let lock = AbortLock::prepare();
+    let sudoku = Sudoku::empty(2).set((0, 0), 1);
+    assert_eq!(None, LogicSolver.solve(&sudoku, &lock));
+    assert!(BruteForceSolver.solve(&sudoku, &lock).is_some_and(|s| s.is_solved()));
+  }
+}
@@ -0,0 +1,64 @@
+use std::time::{Duration, Instant};
+
+use crate::abort_lock::AbortLock;
+use crate::solver::*;
+use crate::sudoku::Sudoku;
+
+/// Nodes a single run may visit, so plain ordering can not stall the comparison
+/// on puzzles built against brute force
+pub const NODE_LIMIT: u64 = 200_000;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct HeuristicRun {
+  pub stats: SolveStats,
+  pub duration: Duration,
+  /// False when the run was aborted or hit `NODE_LIMIT` before finding a solution
+  pub solved: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct HeuristicComparison {
+  pub row_major: HeuristicRun,
+  pub mrv: HeuristicRun,
+}
+
+fn run(sudoku: Sudoku, order: FillOrder, lock: &AbortLock) -> HeuristicRun {
+  let start = Instant::now();
+  let (result, stats) = solution_stats_limited(sudoku, order, NODE_LIMIT, lock);
+  HeuristicRun { stats, duration: start.elapsed(), solved: result.is_some() }
+}
+
+/// Solves the same sudoku branching in reading order and by fewest candidates
+pub fn compare_heuristics(sudoku: Sudoku, lock: &AbortLock) -> HeuristicComparison {
+  HeuristicComparison {
+    row_major: run(sudoku.clone(), FillOrder::RowMajor, lock),
+    mrv: run(sudoku, FillOrder::MRV, lock),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::bench::*;
+
+  #[test]
+  fn mrv_beats_row_major_on_hard_for_brute_force() {
+    let data 
+      = "".to_string()
+      + "   " + "   " + "   "
+      + "   " + "  3" + " 85"
+      + "  1" + " 2 " + "   "
+
+      + "   " + "5 7" + "   "
+      + "  4" + "   " + "1  "
+      + " 9 " + "   " + "   "
+
+      + "5  " + "   " + " 73"
+      + "  2" + " 1 " + "   "
+      + "   " + " 4 " + "  9";
+
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    let comparison = compare_heuristics(sudoku, &AbortLock::prepare());
+    assert!(comparison.mrv.solved);
+    assert!(comparison.mrv.stats.nodes < comparison.row_major.stats.nodes);
+  }
+}
@@ -0,0 +1,123 @@
+use crate::abort_lock::AbortLock;
+use crate::solver::solution_with_effort;
+use crate::sudoku::Sudoku;
+use crate::techniques::{rate_difficulty, TechniqueKind};
+
+/// Singles only puzzles with at most this many candidates per empty field are easy
+const EASY_MAX_AVERAGE_CANDIDATES: f32 = 2.5;
+
+/// Singles only puzzles with at most this many candidates per empty field are medium
+const MEDIUM_MAX_AVERAGE_CANDIDATES: f32 = 3.0;
+
+/// Hardest technique still allowed for a medium puzzle
+const MEDIUM_MAX_TECHNIQUE: TechniqueKind = TechniqueKind::HiddenSingle;
+
+/// Hardest technique still allowed for a hard puzzle
+const HARD_MAX_TECHNIQUE: TechniqueKind = TechniqueKind::Claiming;
+
+/// Puzzles which need guessing are diabolical once brute force backtracks this often
+const DIABOLICAL_MIN_BACKTRACKS: u64 = 1000;
+
+/// Label of a puzzle shown to the player
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Difficulty {
+  Easy,
+  Medium,
+  Hard,
+  Expert,
+  Diabolical,
+}
+
+/// Rates the puzzle by the hardest technique needed to solve it, how crowded its
+/// candidates are and, when logic is not enough, by how much brute force backtracks
+pub fn classify(sudoku: Sudoku, lock: &AbortLock) -> Difficulty {
+  let (_, _, average_candidates) = sudoku.candidate_stats();
+  let (hardest, solved) = rate_difficulty(sudoku.clone(), lock);
+
+  if !solved {
+    let backtracks = solution_with_effort(sudoku, lock)
+      .map_or(u64::MAX, |(_, backtracks)| backtracks);
+    if backtracks < DIABOLICAL_MIN_BACKTRACKS {
+      return Difficulty::Expert;
+    }
+    return Difficulty::Diabolical;
+  }
+
+  match hardest {
+    Some(kind) if kind > HARD_MAX_TECHNIQUE => Difficulty::Expert,
+    Some(kind) if kind > MEDIUM_MAX_TECHNIQUE => Difficulty::Hard,
+    _ if average_candidates <= EASY_MAX_AVERAGE_CANDIDATES => Difficulty::Easy,
+    _ if average_candidates <= MEDIUM_MAX_AVERAGE_CANDIDATES => Difficulty::Medium,
+    _ => Difficulty::Hard,
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::difficulty::*;
+
+  #[test]
+  fn medium_sudoku_is_medium() {
+    let data 
+      = "".to_string()
+      + "  8" + "  3" + "461"
+      + "2 6" + " 84" + "   "
+      + "3  " + "  7" + " 9 "
+
+      + " 3 " + "75 " + "68 "
+      + " 87" + " 1 " + "   "
+      + " 5 " + " 4 " + "13 "
+
+      + "  9" + "27 " + "31 "
+      + "   " + "   " + "   "
+      + "763" + "4 1" + "8 2";
+
+    let lock = AbortLock::prepare();
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    assert_eq!(Difficulty::Medium, classify(sudoku.clone(), &lock));
+
+    let almost_solved = crate::solver::solution(sudoku, &lock).unwrap()
+      .clear((0, 0)).clear((4, 4)).clear((8, 8));
+    assert_eq!(Difficulty::Easy, classify(almost_solved, &lock));
+  }
+
+  #[test]
+  fn hard_sudoku_is_hard() {
+    let data 
+      = "".to_string()
+      + "3  " + " 2 " + "   "
+      + "  7" + "6  " + "  3"
+      + "6  " + "9  " + "  4"
+
+      + "2  " + "1  " + "6  "
+      + "9  " + "8 3" + " 5 "
+      + "  5" + "4  " + "7  "
+
+      + " 8 " + "  9" + " 1 "
+      + "   " + " 7 " + "2  "
+      + " 4 " + "  6" + "3  ";
+
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    assert_eq!(Difficulty::Hard, classify(sudoku, &AbortLock::prepare()));
+  }
+
+  #[test]
+  fn hardest_for_logic_is_diabolical() {
+    let data 
+      = "".to_string()
+      + "8  " + "   " + "   "
+      + "  3" + "6  " + "   "
+      + " 7 " + "  9" + "2  "
+
+      + " 5 " + "  7" + "   "
+      + "   " + " 45" + "7  "
+      + "   " + "1  " + " 3 "
+
+      + "  1" + "   " + " 68"
+      + "  8" + "5  " + " 1 "
+      + " 9 " + "   " + "4  ";
+
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    assert_eq!(Difficulty::Diabolical, classify(sudoku, &AbortLock::prepare()));
+  }
+}
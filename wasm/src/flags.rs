@@ -1,7 +1,7 @@
 use std::cmp::Ordering;
 
 /// Stores flags from 1 to 30
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub struct Flags {
   flag: u32
 }
@@ -11,14 +11,45 @@ impl Flags {
     Flags { flag: 1 }
   }
 
+  /// Builds flags from a raw mask where bit `i` stands for digit `i + 1`
+  pub fn from_bits(bits: u32) -> Self {
+    Flags { flag: (bits << 1) | 1 }
+  }
+
+  /// Raw mask where bit `i` stands for digit `i + 1`, so `size() == bits().count_ones()`
+  pub fn bits(&self) -> u32 {
+    self.flag >> 1
+  }
+
   pub fn set(self, pos: u8) -> Self {
     Flags { flag: self.flag | 1 << pos }
   }
 
+  pub fn unset(self, pos: u8) -> Self {
+    Flags { flag: self.flag & !(1 << pos) }
+  }
+
+  pub fn contains(self, pos: u8) -> bool {
+    self.flag & (1 << pos) != 0
+  }
+
   pub fn or(self, other: Self) -> Self {
     Flags { flag: self.flag | other.flag }
   }
 
+  /// Checks whether no digit is stored
+  pub fn is_empty(self) -> bool {
+    self.bits() == 0
+  }
+
+  /// The only stored digit, `None` when there are no digits or more of them
+  pub fn singleton(self) -> Option<u8> {
+    match self.bits() {
+      bits if bits.count_ones() == 1 => Some(bits.trailing_zeros() as u8 + 1),
+      _ => None
+    }
+  }
+
   pub fn size(mut self) -> u8 {
     let mut count = 0;
     while  self.flag != 0 {
@@ -28,11 +59,12 @@ impl Flags {
     count - 1
   }
 
+  /// Stored digits in ascending order
   pub fn to_vec(mut self) -> Vec<u8> {
     let mut res = vec![];
     let mut i = 0;
     while self.flag != 0 {
-      self.flag = self.flag >> 1;
+      self.flag >>= 1;
       i += 1;
       if self.flag % 2 == 1 {
         res.push(i);
@@ -41,6 +73,19 @@ impl Flags {
     res
   }
 
+  /// Stored digits in ascending order, same as `to_vec` without allocating
+  pub fn iter(&self) -> impl Iterator<Item=u8> {
+    let mut bits = self.bits();
+    std::iter::from_fn(move || match bits {
+      0 => None,
+      _ => {
+        let digit = bits.trailing_zeros() as u8 + 1;
+        bits &= bits - 1;
+        Some(digit)
+      }
+    })
+  }
+
   pub fn from_vec(v: Vec<u8>) -> Self {
     v.iter().fold(Flags::empty(), |flags, val| flags.set(*val))
   }
@@ -49,7 +94,7 @@ impl Flags {
     let mut res = vec![];
     let mut i = 0;
     while self.flag != 0 {
-      self.flag = self.flag >> 1;
+      self.flag >>= 1;
       i += 1;
       if self.flag % 2 == 1 {
         res.push(i);
@@ -119,7 +164,47 @@ mod test {
         let by_flags = flags.clone().iter()
           .fold(Flags::empty(), |acc, f| acc.set(f.0));
         let by_set = BTreeSet::from_iter(flags.into_iter());
-        BTreeSet::from_iter(by_flags.to_vec().into_iter().map(|f| Flag(f))) == by_set
+        BTreeSet::from_iter(by_flags.to_vec().into_iter().map(Flag)) == by_set
+      }
+    }
+
+    #[test]
+    fn bits_mapping() {
+      assert_eq!(0b1, Flags::empty().set(1).bits());
+      assert_eq!(0b1_0000_0000, Flags::empty().set(9).bits());
+      assert_eq!(Flags::empty().set(1).set(9), Flags::from_bits(0b1_0000_0001));
+    }
+
+    #[test]
+    fn singleton_and_empty() {
+      assert!(Flags::empty().is_empty());
+      assert_eq!(None, Flags::empty().singleton());
+      assert!(!Flags::empty().set(7).is_empty());
+      assert_eq!(Some(7), Flags::empty().set(7).singleton());
+      assert_eq!(Some(1), Flags::empty().set(1).singleton());
+      assert_eq!(None, Flags::empty().set(2).set(5).singleton());
+      assert_eq!(Some(5), Flags::empty().set(2).set(5).unset(2).singleton());
+      assert!(Flags::empty().set(3).unset(3).is_empty());
+    }
+
+    quickcheck! {
+      fn prop_bits_keep_size(flags: Flags, len: Flag) -> bool {
+        let mask = (1_u32 << len.0) - 1;
+        flags.size() == flags.bits().count_ones() as u8
+          && Flags::from_bits(flags.bits()) == flags
+          && flags.inverse(len.0).bits() == flags.bits() ^ mask
+      }
+    }
+
+    quickcheck! {
+      fn prop_to_vec_is_ascending(flags: Flags) -> bool {
+        flags.to_vec().windows(2).all(|w| w[0] < w[1])
+      }
+    }
+
+    quickcheck! {
+      fn prop_iter_same_as_to_vec(flags: Flags) -> bool {
+        flags.iter().collect::<Vec<_>>() == flags.to_vec()
       }
     }
 
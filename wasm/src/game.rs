@@ -0,0 +1,188 @@
+use crate::flags::Flags;
+use crate::sudoku::*;
+
+/// Single placement remembering what the field held before
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Move {
+  pos: Position,
+  previous: u8,
+  digit: u8,
+}
+
+/// Board of a game in progress with undo and redo of player moves
+#[derive(Clone, Debug)]
+pub struct GameState {
+  sudoku: Sudoku,
+  history: Vec<Move>,
+  undone: Vec<Move>,
+}
+
+impl GameState {
+  pub fn new(sudoku: Sudoku) -> Self {
+    GameState { sudoku, history: vec![], undone: vec![] }
+  }
+
+  pub fn sudoku(&self) -> &Sudoku {
+    &self.sudoku
+  }
+
+  /// Writes digit into the field, this drops moves which could be redone
+  pub fn push(&mut self, pos: Position, digit: u8) {
+    let previous = self.sudoku.at(pos);
+    self.sudoku = self.sudoku.clone().set(pos, digit);
+    self.history.push(Move { pos, previous, digit });
+    self.undone.clear();
+  }
+
+  /// Reverts the last move restoring what the field held before it,
+  /// returns the reverted placement
+  pub fn undo(&mut self) -> Option<(Position, u8)> {
+    let last = self.history.pop()?;
+    self.sudoku = self.sudoku.clone().set(last.pos, last.previous);
+    self.undone.push(last);
+    Some((last.pos, last.digit))
+  }
+
+  /// Applies again the most recently undone move, returns the placement
+  pub fn redo(&mut self) -> Option<(Position, u8)> {
+    let next = self.undone.pop()?;
+    self.sudoku = self.sudoku.clone().set(next.pos, next.digit);
+    self.history.push(next);
+    Some((next.pos, next.digit))
+  }
+}
+
+/// Board of a saved game telling original clues apart from digits the player wrote
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PlayBoard {
+  sudoku: Sudoku,
+  /// Givens of every row, column `x` is stored as flag `x + 1`
+  given: Vec<Flags>,
+}
+
+impl PlayBoard {
+  /// Every filled field of the puzzle becomes a given
+  pub fn new(puzzle: Sudoku) -> Self {
+    let given = (0 .. puzzle.board_size())
+      .map(|y| (0 .. puzzle.board_size())
+        .filter(|x| puzzle.at((*x, y)) != 0)
+        .fold(Flags::empty(), |flags, x| flags.set(x as u8 + 1)))
+      .collect();
+    PlayBoard { sudoku: puzzle, given }
+  }
+
+  pub fn sudoku(&self) -> &Sudoku {
+    &self.sudoku
+  }
+
+  pub fn is_given(&self, (x, y): Position) -> bool {
+    self.given[y].contains(x as u8 + 1)
+  }
+
+  /// Writes the player's digit into the field, 0 erases it. Givens can't be changed,
+  /// the rules are not checked so the player is free to make mistakes.
+  pub fn try_set(&mut self, pos: Position, digit: u8) -> Result<(), PlacementError> {
+    if self.is_given(pos) {
+      return Err(PlacementError::Given);
+    }
+    if digit as usize > self.sudoku.board_size() {
+      return Err(PlacementError::DigitOutOfRange(digit));
+    }
+    self.sudoku.put(pos, digit);
+    Ok(())
+  }
+
+  /// Givens and the player's digits as two fields in the line format of
+  /// `Sudoku::save` joined with `;`
+  pub fn to_save_string(&self) -> String {
+    let entries = self.sudoku.iter()
+      .filter(|(_, pos)| self.is_given(*pos))
+      .fold(self.sudoku.clone(), |entries, (_, pos)| entries.set(pos, 0));
+    let givens = self.sudoku.iter()
+      .filter(|(_, pos)| !self.is_given(*pos))
+      .fold(self.sudoku.clone(), |givens, (_, pos)| givens.set(pos, 0));
+    format!("{};{}", givens.save(), entries.save())
+  }
+
+  /// Reads back the output of `to_save_string`, the player's digit on a given field
+  /// is reported as an invalid character
+  pub fn from_save_string(data: &str, box_size: u8) -> Result<PlayBoard, LoadError> {
+    let (givens, entries) = data.split_once(';').unwrap_or((data, ""));
+    let mut board = PlayBoard::new(Sudoku::load_strict(givens, box_size)?);
+    let entries = Sudoku::load_strict(entries, box_size)?;
+    for (digit, (x, y)) in entries.iter().filter(|(digit, _)| *digit != 0) {
+      if board.try_set((x, y), digit).is_err() {
+        let index = givens.chars().count() + 1 + y * board.sudoku.board_size() + x;
+        let character = data.chars().nth(index).unwrap_or(' ');
+        return Err(LoadError::InvalidCharacter { index, character });
+      }
+    }
+    Ok(board)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::game::*;
+
+  #[test]
+  fn undo_redo_sequence() {
+    let mut game = GameState::new(Sudoku::empty(2));
+    game.push((0, 0), 1);
+    game.push((1, 0), 2);
+    game.push((0, 0), 3);
+
+    assert_eq!(Some(((0, 0), 3)), game.undo());
+    assert_eq!(1, game.sudoku().at((0, 0)));
+    assert_eq!(Some(((1, 0), 2)), game.undo());
+    assert_eq!(0, game.sudoku().at((1, 0)));
+
+    assert_eq!(Some(((1, 0), 2)), game.redo());
+    assert_eq!(Some(((0, 0), 3)), game.redo());
+    assert_eq!(None, game.redo());
+    assert_eq!(Sudoku::empty(2).set((0, 0), 3).set((1, 0), 2), *game.sudoku());
+  }
+
+  #[test]
+  fn push_clears_redo() {
+    let mut game = GameState::new(Sudoku::empty(2));
+    game.push((0, 0), 1);
+    game.push((1, 0), 2);
+    game.undo();
+
+    game.push((2, 0), 3);
+    assert_eq!(None, game.redo());
+    assert_eq!(Some(((2, 0), 3)), game.undo());
+    assert_eq!(Some(((0, 0), 1)), game.undo());
+    assert_eq!(None, game.undo());
+    assert_eq!(Sudoku::empty(2), *game.sudoku());
+  }
+
+  #[test]
+  fn play_board_round_trip() {
+    let mut board = PlayBoard::new(Sudoku::load("12  341 2 43    ", 2));
+    assert_eq!(Ok(()), board.try_set((2, 0), 4));
+    assert_eq!(Ok(()), board.try_set((3, 3), 2));
+    assert_eq!(Ok(()), board.try_set((3, 3), 0));
+    assert_eq!(Ok(()), board.try_set((0, 3), 4));
+
+    let saved = board.to_save_string();
+    assert_eq!("12  341 2 43    ;  4         4   ", saved);
+    let loaded = PlayBoard::from_save_string(&saved, 2).unwrap();
+    assert_eq!(board, loaded);
+    assert!(loaded.is_given((1, 0)));
+    assert!(!loaded.is_given((2, 0)));
+
+    assert_eq!(Err(LoadError::InvalidCharacter { index: 18, character: '3' }),
+      PlayBoard::from_save_string("12  341 2 43    ; 3              ", 2));
+  }
+
+  #[test]
+  fn givens_are_locked() {
+    let mut board = PlayBoard::new(Sudoku::load("12  341 2 43    ", 2));
+    assert_eq!(Err(PlacementError::Given), board.try_set((0, 0), 3));
+    assert_eq!(Err(PlacementError::Given), board.try_set((2, 2), 0));
+    assert_eq!(Err(PlacementError::DigitOutOfRange(5)), board.try_set((3, 0), 5));
+    assert_eq!(Sudoku::load("12  341 2 43    ", 2), *board.sudoku());
+  }
+}
@@ -0,0 +1,274 @@
+use crate::sudoku::*;
+use crate::solver::has_unique_solution;
+use crate::techniques::solve_logically;
+use crate::abort_lock::AbortLock;
+use itertools::Itertools;
+
+/// Part of the fields kept as clues for difficulties 1 to 5, the hardest level
+/// removes clues for as long as the solution stays unique
+const CLUE_FRACTIONS: [f32; 5] = [0.55, 0.47, 0.40, 0.34, 0.0];
+
+/// Hardest difficulty which still has to be solvable without guessing
+const LOGICAL_DIFFICULTY: u8 = 2;
+
+/// Symmetry of the clue layout, the digits themselves don't have to follow it
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Symmetry {
+  /// Layout looks the same after a half turn, as in most newspaper puzzles
+  Rotational,
+  /// Layout looks the same after every quarter turn
+  QuarterTurn,
+  /// Left and right side mirror each other
+  Horizontal,
+  /// Top and bottom side mirror each other
+  Vertical,
+  /// Mirrored along the diagonal from top left to bottom right
+  Diagonal,
+}
+
+impl Symmetry {
+  /// Fields the symmetry maps the field onto, the field itself included
+  fn orbit(self, (x, y): Position, board_size: usize) -> Vec<Position> {
+    let last = board_size - 1;
+    let mut orbit = match self {
+      Symmetry::Rotational => vec![(x, y), (last - x, last - y)],
+      Symmetry::QuarterTurn => vec![(x, y), (last - y, x), (last - x, last - y), (y, last - x)],
+      Symmetry::Horizontal => vec![(x, y), (last - x, y)],
+      Symmetry::Vertical => vec![(x, y), (x, last - y)],
+      Symmetry::Diagonal => vec![(x, y), (y, x)],
+    };
+    orbit.sort_unstable();
+    orbit.dedup();
+    orbit
+  }
+}
+
+/// Checks that clues are laid out with the symmetry, so every field it maps a clue
+/// onto holds a clue as well
+pub fn clue_symmetry(sudoku: &Sudoku, symmetry: Symmetry) -> bool {
+  sudoku.iter()
+    .filter(|(digit, _)| *digit != 0)
+    .all(|(_, pos)| symmetry.orbit(pos, sudoku.board_size()).into_iter().all(|image| sudoku.at(image) != 0))
+}
+
+/// Xorshift generator, so boards made from a seed are the same on every target
+pub(crate) struct Random {
+  state: u64
+}
+
+impl Random {
+  pub fn new(seed: u64) -> Self {
+    // Spread the seed with splitmix64, xorshift must never start from zero
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    Random { state: (z ^ (z >> 31)) | 1 }
+  }
+
+  pub fn next(&mut self) -> u64 {
+    self.state ^= self.state << 13;
+    self.state ^= self.state >> 7;
+    self.state ^= self.state << 17;
+    self.state
+  }
+
+  /// Number from `0 .. n`
+  pub fn below(&mut self, n: usize) -> usize {
+    (self.next() % n as u64) as usize
+  }
+
+  pub fn shuffle<A>(&mut self, items: &mut [A]) {
+    for i in (1 .. items.len()).rev() {
+      items.swap(i, self.below(i + 1));
+    }
+  }
+
+  /// Order of lines after swapping whole bands and lines within each band
+  fn line_order(&mut self, box_size: usize) -> Vec<usize> {
+    let mut bands: Vec<usize> = (0 .. box_size).collect();
+    self.shuffle(&mut bands);
+    bands.into_iter()
+      .flat_map(|band| {
+        let mut lines: Vec<usize> = (band * box_size .. (band + 1) * box_size).collect();
+        self.shuffle(&mut lines);
+        lines
+      })
+      .collect()
+  }
+}
+
+/// Relabels digits, permutes bands, stacks and lines within them and possibly
+/// transposes the board. None of these can break a rule, so a solved board
+/// stays solved and a puzzle keeps the same number of solutions. Meant for classic
+/// boards, killer cages are not carried over as relabeling changes their sums.
+pub fn shuffle(sudoku: Sudoku, seed: u64) -> Sudoku {
+  let mut random = Random::new(seed);
+  let board_size = sudoku.board_size();
+
+  let mut digits: Vec<u8> = (1 ..= board_size as u8).collect();
+  random.shuffle(&mut digits);
+  let rows = random.line_order(sudoku.box_size());
+  let columns = random.line_order(sudoku.box_size());
+  let transpose = random.below(2) == 1;
+
+  (0 .. board_size)
+    .flat_map(|y| (0 .. board_size).map(move |x| (x, y)))
+    .fold(Sudoku::empty(sudoku.box_size() as u8), |acc, (x, y)| {
+      let source = if transpose { (columns[y], rows[x]) } else { (columns[x], rows[y]) };
+      match sudoku.at(source) {
+        0 => acc,
+        d => acc.set((x, y), digits[d as usize - 1])
+      }
+    })
+}
+
+/// Solved board picked by the seed, made by shuffling a fixed pattern
+pub fn random_solution(box_size: u8, seed: u64) -> Sudoku {
+  let box_size = box_size as usize;
+  let board_size = box_size * box_size;
+  let pattern = (0 .. board_size)
+    .flat_map(|y| (0 .. board_size).map(move |x| (x, y)))
+    .fold(Sudoku::empty(box_size as u8), |acc, (x, y)| {
+      let digit = (box_size * (y % box_size) + y / box_size + x) % board_size + 1;
+      acc.set((x, y), digit as u8)
+    });
+  shuffle(pattern, seed)
+}
+
+/// Puzzle with a unique solution, the same for the same seed. Clues are taken out
+/// in random order as long as the solution stays unique and more of them are left
+/// than the target of given difficulty (1 to 5). Up to difficulty 2 every clue
+/// needed by the logical solver is kept, so those puzzles never need guessing.
+/// Gives `None` once the lock gets aborted.
+pub fn generate(box_size: u8, difficulty: u8, seed: u64, lock: &AbortLock) -> Option<Sudoku> {
+  let difficulty = difficulty.clamp(1, CLUE_FRACTIONS.len() as u8);
+  let mut random = Random::new(seed);
+  let mut sudoku = random_solution(box_size, random.next());
+  let board_size = sudoku.board_size();
+  let target = (CLUE_FRACTIONS[difficulty as usize - 1] * (board_size * board_size) as f32) as usize;
+
+  let mut positions: Vec<Position> = (0 .. board_size)
+    .flat_map(|y| (0 .. board_size).map(move |x| (x, y)))
+    .collect();
+  random.shuffle(&mut positions);
+
+  for pos in positions {
+    if board_size * board_size - sudoku.remaining_cells() <= target {
+      break;
+    }
+    let removed = sudoku.clear(pos);
+    let logical = difficulty > LOGICAL_DIFFICULTY || solve_logically(removed.clone(), lock).1;
+    if logical && has_unique_solution(&removed, lock) {
+      sudoku = removed;
+    }
+    if lock.is_aborted() {
+      return None;
+    }
+  }
+  Some(sudoku)
+}
+
+/// Puzzle with a unique solution whose clues are laid out with the symmetry, the same
+/// for the same seed. Clues are taken out a whole orbit of the symmetry at a time in
+/// random order, orbits whose removal would break uniqueness or leave fewer than
+/// `target_clues` clues are kept. Gives `None` for unsupported box sizes and once the
+/// lock gets aborted.
+pub fn generate_symmetric(box_size: u8, symmetry: Symmetry, target_clues: usize, seed: u64, 
+    lock: &AbortLock) -> Option<Sudoku> {
+  Sudoku::check_box_size(box_size).ok()?;
+  let mut random = Random::new(seed);
+  let mut sudoku = random_solution(box_size, random.next());
+  let board_size = sudoku.board_size();
+  let mut clues = board_size * board_size;
+
+  let mut orbits: Vec<Vec<Position>> = (0 .. board_size)
+    .flat_map(|y| (0 .. board_size).map(move |x| (x, y)))
+    .map(|pos| symmetry.orbit(pos, board_size))
+    .unique()
+    .collect();
+  random.shuffle(&mut orbits);
+
+  for orbit in orbits {
+    if clues - orbit.len() < target_clues {
+      continue;
+    }
+    let removed = orbit.iter().fold(sudoku.clone(), |acc, pos| acc.clear(*pos));
+    if has_unique_solution(&removed, lock) {
+      sudoku = removed;
+      clues -= orbit.len();
+    }
+    if lock.is_aborted() {
+      return None;
+    }
+  }
+  Some(sudoku)
+}
+
+#[cfg(test)]
+mod test {
+  use crate::generator::*;
+  use crate::solver::solution;
+  use crate::abort_lock::AbortLock;
+
+  #[test]
+  fn shuffled_board_stays_solved() {
+    let data 
+      = "".to_string()
+      + "  8" + "  3" + "461"
+      + "2 6" + " 84" + "   "
+      + "3  " + "  7" + " 9 "
+
+      + " 3 " + "75 " + "68 "
+      + " 87" + " 1 " + "   "
+      + " 5 " + " 4 " + "13 "
+
+      + "  9" + "27 " + "31 "
+      + "   " + "   " + "   "
+      + "763" + "4 1" + "8 2";
+
+    let solved = solution(Sudoku::load(data.as_str(), 3), &AbortLock::prepare()).unwrap();
+    let shuffled = shuffle(solved.clone(), 42);
+    assert!(shuffled.is_solved());
+    assert_ne!(solved, shuffled);
+    assert_eq!(shuffled, shuffle(solved, 42));
+  }
+
+  #[test]
+  fn symmetric_puzzle_is_unique() {
+    let lock = AbortLock::prepare();
+    let symmetries = [Symmetry::Rotational, Symmetry::QuarterTurn, Symmetry::Horizontal, 
+      Symmetry::Vertical, Symmetry::Diagonal];
+    for symmetry in symmetries.iter().copied() {
+      let puzzle = generate_symmetric(2, symmetry, 4, 7, &lock).unwrap();
+      assert!(clue_symmetry(&puzzle, symmetry));
+      assert!(crate::solver::has_unique_solution(&puzzle, &lock));
+      assert!(16 - puzzle.remaining_cells() >= 4);
+    }
+
+    let puzzle = generate_symmetric(3, Symmetry::Rotational, 28, 7, &lock).unwrap();
+    assert!(clue_symmetry(&puzzle, Symmetry::Rotational));
+    let (_, clue) = puzzle.iter().find(|(digit, pos)| *digit != 0 && *pos != (4, 4)).unwrap();
+    assert!(!clue_symmetry(&puzzle.clear(clue), Symmetry::Rotational));
+    assert!(crate::solver::has_unique_solution(&puzzle, &lock));
+    assert!(81 - puzzle.remaining_cells() >= 28);
+    assert_eq!(Some(puzzle), generate_symmetric(3, Symmetry::Rotational, 28, 7, &lock));
+    assert_eq!(None, generate_symmetric(0, Symmetry::Rotational, 0, 7, &lock));
+    assert_eq!(None, generate_symmetric(MAX_BOX_SIZE + 1, Symmetry::Rotational, 0, 7, &lock));
+  }
+
+  #[test]
+  fn generated_puzzle_is_unique() {
+    let lock = AbortLock::prepare();
+    assert!(random_solution(3, 5).is_solved());
+
+    for difficulty in 1 ..= 5 {
+      let puzzle = generate(2, difficulty, 11, &lock).unwrap();
+      assert!(crate::solver::has_unique_solution(&puzzle, &lock));
+      assert_eq!(Some(puzzle), generate(2, difficulty, 11, &lock));
+    }
+    let easy = generate(2, 1, 11, &lock).unwrap();
+    assert!(easy.remaining_cells() <= generate(2, 5, 11, &lock).unwrap().remaining_cells());
+    assert!(crate::techniques::solve_logically(easy, &lock).1);
+    assert_eq!(None, generate(3, 5, 11, &AbortLock::aborted()));
+  }
+}
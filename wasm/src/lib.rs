@@ -3,7 +3,15 @@ pub mod sudoku;
 pub mod solver;
 pub mod flags;
 pub mod matching;
+pub mod techniques;
+pub mod difficulty;
+pub mod generator;
+pub mod game;
+pub mod parse;
+pub mod backend;
 pub mod abort_lock;
+#[cfg(feature = "bench")]
+pub mod bench;
 
 use solver::*;
 use sudoku::Sudoku;
@@ -17,10 +25,11 @@ extern "C" {
 
 #[wasm_bindgen]
 pub fn solve_sudoku(sudoku_str: &str, size: u8, lock: &AbortLock) -> Option<String> {
+  Sudoku::check_box_size(size).ok()?;
   let sudoku = Sudoku::load(sudoku_str, size);
 
   if lock.is_locked() {
-    solution(sudoku, &lock).map(|s| s.save())
+    solution(sudoku, lock).map(|s| s.save())
   } else {
     None
   }
@@ -28,14 +37,12 @@ pub fn solve_sudoku(sudoku_str: &str, size: u8, lock: &AbortLock) -> Option<Stri
 
 #[wasm_bindgen]
 pub fn get_hint(sudoku_str: &str, size: u8, max_level: u8, lock: &AbortLock) -> Option<Hint> {
+  Sudoku::check_box_size(size).ok()?;
   let sudoku = Sudoku::load(sudoku_str, size);
 
   if lock.is_locked() {
-    match hint(sudoku, max_level, &lock) {
-      None => None,
-      Some((digit, (x, y), level)) => 
-        Some (Hint { x: x as u8, y: y as u8, digit, level })
-    }
+    hint(sudoku, max_level, lock)
+      .map(|(digit, (x, y), level)| Hint { x: x as u8, y: y as u8, digit, level })
   } else {
     None
   }
@@ -43,11 +50,12 @@ pub fn get_hint(sudoku_str: &str, size: u8, max_level: u8, lock: &AbortLock) ->
 
 #[wasm_bindgen]
 pub fn get_all_solutions(sudoku_str: &str, size: u8, lock: &AbortLock) -> Option<Vec<String>> {
+  Sudoku::check_box_size(size).ok()?;
   let sudoku = Sudoku::load(sudoku_str, size);
 
   let mut solutions = vec![];
   if lock.is_locked() {
-    let iter = solution_iter(sudoku, &lock);
+    let iter = solution_iter(sudoku, lock);
     for s in iter {
       solutions.push(s.save())
     }
@@ -60,6 +68,20 @@ pub fn get_all_solutions(sudoku_str: &str, size: u8, lock: &AbortLock) -> Option
   }
 }
 
+/// New puzzle with a unique solution in the line format, difficulty goes from 1 to 5
+/// and the same seed always gives the same puzzle. Gives nothing for unsupported box sizes
+/// and once the lock gets aborted.
+#[wasm_bindgen]
+pub fn generate_puzzle(size: u8, difficulty: u8, seed: u64, lock: &AbortLock) -> Option<String> {
+  Sudoku::check_box_size(size).ok()?;
+
+  if lock.is_locked() {
+    generator::generate(size, difficulty, seed, lock).map(|s| s.save())
+  } else {
+    None
+  }
+}
+
 #[derive(Clone)]
 #[wasm_bindgen]
 pub struct Hint {
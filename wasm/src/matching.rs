@@ -15,11 +15,8 @@ fn set_in_vec<A>(mut vec: Vec<A>, pos: usize, val: A) -> Vec<A>{
 pub fn get_perfect_matching(connections: &Vec<Vec<usize>>) -> Option<Vec<Option<usize>>> {
   let empty_matching = vec![None; connections.len()];
   let visits = vec![false; connections.len()];
-  (0 .. connections.len()).fold(Some(empty_matching), |matching, pos| {
-    match matching {
-      None => None,
-      Some(m) => extend(pos, connections, m, &mut visits.clone())
-    }
+  (0 .. connections.len()).try_fold(empty_matching, |matching, pos| {
+    extend(pos, connections, matching, &mut visits.clone())
   })
 }
 
@@ -32,21 +29,19 @@ pub fn extend( to_extend: usize, connections: &Vec<Vec<usize>>, matching: Vec<Op
   }
 
   let simple_conn = connections[to_extend].iter()
-    .filter(|c| matching[**c].is_none())
-    .map(|x| *x)
-    .next();
+    .find(|c| matching[**c].is_none())
+    .copied();
 
   match simple_conn {
     Some(c) => Some(set_in_vec(matching, c, Some(to_extend))),
     None => {
-      return connections[to_extend].iter()
+      connections[to_extend].iter()
         .map(|&c| {
           let broken = matching[c]?;
           let updated_matching 
             = set_in_vec(matching.clone(), c, Some(to_extend));
           extend(broken, connections, updated_matching, visited)
-        }).filter(|o| o.is_some())
-        .next()?;
+        }).find(|o| o.is_some())?
     }
   }
 }
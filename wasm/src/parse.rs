@@ -0,0 +1,151 @@
+use std::fmt;
+
+use crate::sudoku::Sudoku;
+
+/// Box sizes which are detected from the number of cells
+const DETECTED_BOX_SIZES: [u8; 3] = [2, 3, 4];
+
+/// Reasons why a text could not be parsed into a sudoku
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+  /// Character at given index of the input is outside of the grammar or its digit
+  /// does not fit on the detected board
+  UnexpectedCharacter { index: usize, character: char },
+  /// Number of cells does not match any supported board
+  WrongLength { found: usize },
+  /// Input reads as a board of either box size depending on what spaces mean
+  AmbiguousSize { with_spaces: u8, without_spaces: u8 },
+}
+
+impl fmt::Display for ParseError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ParseError::UnexpectedCharacter { index, character } =>
+        write!(f, "unexpected character {:?} at index {}", character, index),
+      ParseError::WrongLength { found } =>
+        write!(f, "expected 16, 81 or 256 cells, found {}", found),
+      ParseError::AmbiguousSize { with_spaces, without_spaces } => {
+        let cells = |box_size: u8| (box_size as usize).pow(4);
+        write!(f, "input reads as {} cells with spaces as empty fields and as {} cells without them",
+          cells(*with_spaces), cells(*without_spaces))
+      }
+    }
+  }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Token {
+  Cell(u8),
+  Space,
+}
+
+/// Cells and spaces of the input in the character grammar of `Sudoku::load_pretty`
+fn tokenize(input: &str) -> Result<Vec<(usize, char, Token)>, ParseError> {
+  input.chars().enumerate()
+    .filter(|(_, c)| !Sudoku::is_separator(*c))
+    .map(|(index, character)| match (character, Sudoku::cell_digit(character)) {
+      (' ', _) => Ok((index, character, Token::Space)),
+      (_, Some(digit)) => Ok((index, character, Token::Cell(digit))),
+      (_, None) => Err(ParseError::UnexpectedCharacter { index, character }),
+    })
+    .collect()
+}
+
+fn detect_box_size(cells: usize) -> Option<u8> {
+  DETECTED_BOX_SIZES.iter().copied()
+    .find(|&box_size| (box_size as usize).pow(4) == cells)
+}
+
+/// Parses sudoku of box size 2, 3 or 4 from arbitrary text like a board pasted into the web app.
+/// Box size is detected from the number of cells, the characters are those of
+/// `Sudoku::load_pretty`:
+///
+/// ```text
+/// input     = { cell | separator | space }
+/// cell      = digit | empty
+/// digit     = "1" .. "9" | "A" .. "G"   (10 to 16, only on 16x16 boards)
+/// empty     = "." | "0"
+/// separator = line break | tab | "|" | "-" | "+" | "," | ";"
+/// space     = " "
+/// ```
+///
+/// Space marks an empty field in the format of `Sudoku::save`, but it is also commonly used
+/// to separate cells. Both readings are tried and the one giving 16, 81 or 256 cells wins,
+/// when both of them do the input is rejected as ambiguous. Clashing clues are not rejected,
+/// use `Sudoku::conflicts` for that.
+pub fn parse(input: &str) -> Result<Sudoku, ParseError> {
+  let tokens = tokenize(input)?;
+  let without_spaces = tokens.iter().filter(|(_, _, t)| *t != Token::Space).count();
+  let box_size = match (detect_box_size(tokens.len()), detect_box_size(without_spaces)) {
+    (Some(with_spaces), Some(without_spaces)) if with_spaces != without_spaces =>
+      return Err(ParseError::AmbiguousSize { with_spaces, without_spaces }),
+    (Some(box_size), _) => box_size,
+    (None, Some(box_size)) => box_size,
+    (None, None) => return Err(ParseError::WrongLength { found: without_spaces }),
+  };
+  let spaces_are_cells = tokens.len() == (box_size as usize).pow(4);
+
+  let board_size = box_size as usize * box_size as usize;
+  let mut sudoku = Sudoku::empty(box_size);
+  let cells = tokens.into_iter()
+    .filter_map(|(index, character, token)| match token {
+      Token::Cell(digit) => Some((index, character, digit)),
+      Token::Space if spaces_are_cells => Some((index, character, 0)),
+      Token::Space => None,
+    });
+  for (i, (index, character, digit)) in cells.enumerate() {
+    if digit as usize > board_size {
+      return Err(ParseError::UnexpectedCharacter { index, character });
+    }
+    sudoku.put((i % board_size, i / board_size), digit);
+  }
+  Ok(sudoku)
+}
+
+#[cfg(test)]
+mod test {
+  use crate::parse::*;
+
+  #[test]
+  fn detects_classic_board() {
+    let board = "53..7....\n".to_owned() +
+      "6..195...\n" +
+      ".98....6.\n" +
+      "8...6...3\n" +
+      "4..8.3..1\n" +
+      "7...2...6\n" +
+      ".6....28.\n" +
+      "...419..5\n" +
+      "....8..79\n";
+    let sudoku = parse(&board).unwrap();
+    assert_eq!(3, sudoku.box_size());
+    assert_eq!(5, sudoku.at((0, 0)));
+    assert_eq!(9, sudoku.at((8, 8)));
+    assert_eq!(Ok(sudoku.clone()), parse(&sudoku.save()));
+    assert_eq!(Ok(sudoku.clone()), parse(&sudoku.pretty()));
+    assert_eq!(Sudoku::load_pretty(&board, 3).ok(), parse(&board).ok());
+  }
+
+  #[test]
+  fn spaces_separating_cells() {
+    assert_eq!(Ok(Sudoku::load("12  341 2 43    ", 2)), parse("1 2 0 0, 3 4 1 0, 2 0 4 3, 0 0 0 0"));
+    assert_eq!(Err(ParseError::AmbiguousSize { with_spaces: 3, without_spaces: 2 }),
+      parse(&(" ".repeat(65) + "1234123412341234")));
+  }
+
+  #[test]
+  fn length_mismatch() {
+    assert_eq!(Err(ParseError::WrongLength { found: 80 }), parse(&".".repeat(80)));
+    assert_eq!(Err(ParseError::WrongLength { found: 0 }), parse(" | \n"));
+  }
+
+  #[test]
+  fn illegal_character() {
+    let board = "12..\n34x.\n....\n....";
+    assert_eq!(Err(ParseError::UnexpectedCharacter { index: 7, character: 'x' }), parse(board));
+    assert_eq!(Err(ParseError::UnexpectedCharacter { index: 2, character: '5' }),
+      parse(&("125.".to_owned() + &".".repeat(12))));
+    assert_eq!(Err(ParseError::UnexpectedCharacter { index: 0, character: 'a' }),
+      parse(&("a".to_owned() + &".".repeat(255))));
+  }
+}
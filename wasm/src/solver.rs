@@ -4,90 +4,846 @@ use crate::sudoku::*;
 use crate::flags::*;
 use crate::matching::has_perfect_matching;
 use crate::abort_lock::*;
+use crate::techniques::propagate;
 use std::cmp::Ordering::Equal;
+use std::collections::HashMap;
+use std::fmt;
 use itertools::Itertools;
 
-pub fn solution(sudoku: Sudoku, lock: &AbortLock) -> Option<Sudoku> {
-  if lock.is_aborted() {
-    return None;
+/// Strategy for picking the field the solver branches on
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FillOrder {
+  /// First empty field in reading order
+  RowMajor,
+  /// Field with the fewest remaining candidates
+  MRV,
+  /// Field with the fewest candidates, without first checking each of them for
+  /// direct contradictions
+  PlainMRV,
+  /// Like `MRV`, ties are broken in favour of fields with the most filled peers
+  LocalMRV,
+  /// Field `MRV` picks when its digit is forced, otherwise the first empty field in
+  /// reading order, so completions are found in lexicographic order
+  Lexicographic,
+}
+
+/// How `solution_with_strategy` picks the field to branch on
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BranchStrategy {
+  /// Field with the fewest candidates left after checking each of them for direct
+  /// contradictions, the branching of `FillOrder::MRV`
+  MinCandidates,
+  /// Among fields with the fewest of those candidates the one next to most filled
+  /// fields, so an animated solve grows around the clues instead of jumping around the board
+  MinCandidatesThenLocality,
+}
+
+/// Reasons why `solve` gives no solution
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum SolveError {
+  /// Board follows the rules, but can't be completed
+  Unsolvable,
+  /// Lock got aborted before the search finished
+  Aborted,
+  /// Given digits already clash, every clashing pair of positions is listed
+  InvalidBoard(Vec<(Position, Position)>),
+}
+
+impl fmt::Display for SolveError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      SolveError::Unsolvable => write!(f, "sudoku has no solution"),
+      SolveError::Aborted => write!(f, "solving was aborted"),
+      SolveError::InvalidBoard(pairs) => {
+        let described = pairs.iter()
+          .map(|(p1, p2)| format!("{:?} and {:?}", p1, p2))
+          .join(", ");
+        write!(f, "conflicting digits at {}", described)
+      }
+    }
+  }
+}
+
+/// Counters gathered while searching for a solution
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct SolveStats {
+  /// Number of visited search nodes
+  pub nodes: u64,
+  /// Number of tried digits which led to a dead end
+  pub backtracks: u64,
+}
+
+/// Shape of the search tree `solution` went through, for estimating difficulty
+/// beyond the hint level
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct SearchMetrics {
+  /// Number of visited search nodes
+  pub nodes: u64,
+  /// Most digits written on top of each other by the search
+  pub max_depth: usize,
+  /// Number of nodes where more than one digit had to be tried
+  pub guesses: u64,
+}
+
+/// Unsolvability checks a search makes between two looks at the abort lock. A single
+/// search node checks every candidate of every empty field, so the lock is consulted
+/// during that work too and not only once per node.
+pub const ABORT_CHECK_INTERVAL: u64 = 16;
+
+/// Most candidates the best field may have for every digit of every empty field to
+/// be checked for contradictions before branching. Above it the check rarely finds a
+/// forced field and costs more than the nodes it saves, so the search branches
+/// without it.
+pub const LOOKAHEAD_MAX_CANDIDATES: u8 = 3;
+
+/// Matching results kept at most, the cache is dropped once it grows past this
+const MATCHING_CACHE_LIMIT: usize = 1 << 16;
+
+/// Work shared between nodes of one search. Perfect matching results are keyed by
+/// candidates of the checked unit, so a unit left untouched by the last `set`
+/// hits the cache and changed units simply miss it. The map only answers lookups
+/// and is never iterated, so it can't change the order of the search.
+#[derive(Default)]
+pub struct SolveState {
+  matchings: HashMap<Vec<Flags>, bool>,
+}
+
+impl SolveState {
+  pub fn new() -> Self {
+    SolveState::default()
+  }
+
+  fn has_perfect_matching(&mut self, unit: Vec<Flags>) -> bool {
+    if let Some(result) = self.matchings.get(&unit) {
+      return *result;
+    }
+    if self.matchings.len() >= MATCHING_CACHE_LIMIT {
+      self.matchings.clear();
+    }
+    let result = has_perfect_matching(unit.clone());
+    self.matchings.insert(unit, result);
+    result
+  }
+}
+
+/// Depth first search shared by the solving functions
+struct Search<'r> {
+  lock: &'r AbortLock,
+  order: FillOrder,
+  node_limit: u64,
+  max_depth: usize,
+  depth: usize,
+  deepest: usize,
+  guesses: u64,
+  lookahead_limit: u8,
+  too_deep: bool,
+  check: AbortCheck<'r>,
+  state: Option<SolveState>,
+  stats: SolveStats,
+  progress: Option<(u64, &'r mut dyn FnMut(u64))>,
+}
+
+impl<'r> Search<'r> {
+  fn new(order: FillOrder, lock: &'r AbortLock) -> Self {
+    Search { 
+      lock, order, node_limit: u64::MAX, max_depth: usize::MAX, depth: 0, deepest: 0, guesses: 0,
+      lookahead_limit: LOOKAHEAD_MAX_CANDIDATES, too_deep: false, check: AbortCheck::new(lock), state: None,
+      stats: SolveStats::default(), progress: None
+    }
+  }
+
+  /// Counts a new node, false when the search should not go on
+  fn enter(&mut self) -> bool {
+    if self.lock.is_aborted() || self.stats.nodes >= self.node_limit || self.too_deep {
+      return false;
+    }
+    self.stats.nodes += 1;
+    if let Some((every, report)) = self.progress.as_mut() {
+      if self.stats.nodes.is_multiple_of(*every) {
+        report(self.stats.nodes);
+      }
+    }
+    true
   }
-  match get_best_options(&sudoku) {
-    None => Some(sudoku),
-    Some((flags, (x, y))) => {
-      flags.to_vec().into_iter().fold(None, |prev, digit| match prev {
-        None => {
-          let updated_sudoku = sudoku.clone().set((x, y), digit);
-          solution(updated_sudoku, lock)
+
+  fn solve(&mut self, sudoku: Sudoku) -> Option<Sudoku> {
+    if self.depth > self.max_depth {
+      self.too_deep = true;
+    }
+    if !self.enter() {
+      return None;
+    }
+
+    let options = next_options(&sudoku, self.order, self.lookahead_limit, &mut self.check, self.state.as_mut());
+    if self.check.aborted {
+      return None;
+    }
+    match options {
+      None => Some(sudoku),
+      Some((flags, pos)) => {
+        self.depth += 1;
+        self.deepest = self.deepest.max(self.depth);
+        if flags.size() > 1 {
+          self.guesses += 1;
         }
-        Some (s) => Some(s)
-      })
+        let result = flags.iter().find_map(|digit| {
+          let updated_sudoku = sudoku.clone().set(pos, digit);
+          let solved = self.solve(updated_sudoku);
+          if solved.is_none() {
+            self.stats.backtracks += 1;
+          }
+          solved
+        });
+        self.depth -= 1;
+        result
+      }
+    }
+  }
+
+  /// Visits completions in search order, stops as soon as `visit` returns false
+  fn visit_all(&mut self, sudoku: Sudoku, visit: &mut impl FnMut(&Sudoku) -> bool) -> bool {
+    if !self.enter() {
+      return false;
+    }
+
+    let options = next_options(&sudoku, self.order, self.lookahead_limit, &mut self.check, self.state.as_mut());
+    if self.check.aborted {
+      return false;
+    }
+    match options {
+      None if sudoku.is_solved() => visit(&sudoku),
+      None => true,
+      Some((flags, pos)) => {
+        flags.iter().all(|digit| {
+          let updated_sudoku = sudoku.clone().set(pos, digit);
+          self.visit_all(updated_sudoku, visit)
+        })
+      }
+    }
+  }
+}
+
+/// Reusable solver for many boards in a row. Searches like `solution`, but boards of
+/// pending branches, candidate lists and matching results are kept between calls
+/// instead of being built again for every board. Unsolvability checks still collect
+/// the candidates of every unit they look at.
+pub struct Solver<'r> {
+  lock: &'r AbortLock,
+  check: AbortCheck<'r>,
+  nodes: u64,
+  lookahead_limit: u8,
+  state: SolveState,
+  current: Option<Sudoku>,
+  options: Vec<(Flags, Position)>,
+  /// Board, branched field and digits not tried yet of every guess, only the first
+  /// `depth` of them are in use, the rest keep their allocations for later
+  frames: Vec<(Sudoku, Position, Flags)>,
+  depth: usize,
+}
+
+/// Counts unsolvability checks and looks at the lock once every `every` of them
+struct AbortCheck<'r> {
+  lock: &'r AbortLock,
+  every: u64,
+  checks: u64,
+  aborted: bool,
+  hook: Option<&'r mut dyn FnMut(u64)>,
+}
+
+impl<'r> AbortCheck<'r> {
+  fn new(lock: &'r AbortLock) -> Self {
+    AbortCheck { lock, every: ABORT_CHECK_INTERVAL, checks: 0, aborted: false, hook: None }
+  }
+
+  /// Counts one check about to be made, true once the lock was seen aborted and
+  /// the check should be skipped
+  fn tick(&mut self) -> bool {
+    if self.aborted {
+      return true;
+    }
+    self.checks += 1;
+    if let Some(hook) = self.hook.as_mut() {
+      hook(self.checks);
+    }
+    if self.checks.is_multiple_of(self.every) {
+      self.aborted = self.lock.is_aborted();
+    }
+    self.aborted
+  }
+}
+
+impl<'r> Solver<'r> {
+  pub fn new(lock: &'r AbortLock) -> Self {
+    let check = AbortCheck::new(lock);
+    Solver { lock, check, nodes: 0, lookahead_limit: LOOKAHEAD_MAX_CANDIDATES, state: SolveState::new(), current: None, options: vec![], frames: vec![], depth: 0 }
+  }
+
+  /// Looks at the abort lock after every `every` unsolvability checks instead of
+  /// `ABORT_CHECK_INTERVAL`, it is always looked at once per search node as well
+  pub fn with_abort_interval(mut self, every: u64) -> Self {
+    self.check.every = every.max(1);
+    self
+  }
+
+  /// Checks candidates of every empty field for contradictions as long as the best
+  /// field has at most `limit` of them instead of `LOOKAHEAD_MAX_CANDIDATES`
+  pub fn with_lookahead_limit(mut self, limit: u8) -> Self {
+    self.lookahead_limit = limit;
+    self
+  }
+
+  /// Calls `hook` with the number of unsolvability checks so far before each of them
+  pub fn with_check_hook(mut self, hook: &'r mut dyn FnMut(u64)) -> Self {
+    self.check.hook = Some(hook);
+    self
+  }
+
+  /// Search nodes visited by the last `solve`
+  pub fn nodes(&self) -> u64 {
+    self.nodes
+  }
+
+  /// Unsolvability checks made by the last `solve`
+  pub fn checks(&self) -> u64 {
+    self.check.checks
+  }
+
+  /// Same result as `solution` for the given board, the same checks are made
+  /// around the search
+  pub fn solve(&mut self, sudoku: &Sudoku) -> Option<Sudoku> {
+    let lock = self.lock;
+    checked_search(sudoku.clone(), lock, |propagated| self.search(&propagated)).ok()
+  }
+
+  /// Search behind `solve`, any board left without empty fields is given back
+  fn search(&mut self, sudoku: &Sudoku) -> Option<Sudoku> {
+    let mut current = self.current.take().unwrap_or_else(|| sudoku.clone());
+    current.copy_from(sudoku);
+    self.depth = 0;
+    self.nodes = 0;
+    self.check.checks = 0;
+    self.check.aborted = false;
+
+    let result = loop {
+      if self.lock.is_aborted() {
+        break None;
+      }
+      self.nodes += 1;
+      match self.best_options(&mut current) {
+        None => break Some(current.clone()),
+        Some((flags, pos)) => {
+          if self.frames.len() == self.depth {
+            self.frames.push((current.clone(), pos, flags));
+          } else {
+            let frame = &mut self.frames[self.depth];
+            frame.0.copy_from(&current);
+            frame.1 = pos;
+            frame.2 = flags;
+          }
+          self.depth += 1;
+          if !self.next_branch(&mut current) {
+            break None;
+          }
+        }
+      }
+    };
+    self.current = Some(current);
+    result
+  }
+
+  /// Moves `current` to the next untried digit, dropping exhausted frames on the way
+  fn next_branch(&mut self, current: &mut Sudoku) -> bool {
+    while self.depth > 0 {
+      let (board, pos, flags) = &mut self.frames[self.depth - 1];
+      if let Some(digit) = flags.iter().next() {
+        *flags = flags.unset(digit);
+        current.copy_from(board);
+        current.put(*pos, digit);
+        return true;
+      }
+      self.depth -= 1;
+    }
+    false
+  }
+
+  /// Same choice as `get_lexicographic_options`, digits are tried on `current` itself
+  /// and taken back instead of checking copies of the board
+  fn best_options(&mut self, current: &mut Sudoku) -> Option<(Flags, Position)> {
+    self.options.clear();
+    self.options.extend(current.iter()
+      .filter(|(d, _)| *d == 0)
+      .map(|(_, pos)| (current.available(pos), pos)));
+
+    let min_option_pos = self.options.iter()
+      .min_by(|(f1, _), (f2, _)| f1.partial_cmp(f2).unwrap_or(Equal))
+      .copied();
+
+    if min_option_pos.is_some_and(|(f, _)| f.size() > 1) {
+      let look_ahead = min_option_pos.is_some_and(|(f, _)| f.size() <= self.lookahead_limit);
+      let (state, check) = (&mut self.state, &mut self.check);
+      let forced = if look_ahead {
+        self.options.iter()
+          .map(|(flags, pos)| (Solver::real_options(current, state, check, *flags, *pos), *pos))
+          .min_by(|(f1, _), (f2, _)| f1.partial_cmp(f2).unwrap_or(Equal))
+      } else {
+        min_option_pos
+      };
+      match forced {
+        Some((flags, _)) if flags.size() > 1 => self.options.iter()
+          .min_by_key(|(_, (x, y))| (*y, *x))
+          .map(|(flags, pos)| (Solver::real_options(current, state, check, *flags, *pos), *pos)),
+        forced => forced
+      }
+    } else {
+      min_option_pos
     }
   }
+
+  /// Candidates of the field which don't make the board unsolvable right away. Once
+  /// the lock is seen aborted none are left, so the search unwinds without more work.
+  fn real_options(current: &mut Sudoku, state: &mut SolveState, check: &mut AbortCheck, flags: Flags, 
+      pos: Position) -> Flags {
+    let real_possibilities = flags.iter()
+      .filter(|digit| {
+        if check.tick() {
+          return false;
+        }
+        current.put(pos, *digit);
+        let unsolvable = is_unsolvable_cached(current, state);
+        current.put(pos, 0);
+        !unsolvable
+      })
+      .collect();
+    Flags::from_vec(real_possibilities)
+  }
+}
+
+/// Finds a completion by trying digits in ascending order, fields whose digit is
+/// forced go first and otherwise the search branches on the first empty field in
+/// reading order. For puzzles with many solutions this gives the lexicographically
+/// smallest one, the first of `solution_iter`. Digits `propagate` finds forced are
+/// filled in before the search starts.
+pub fn solution(sudoku: Sudoku, lock: &AbortLock) -> Option<Sudoku> {
+  solve(sudoku, lock).ok()
+}
+
+/// Same search as `solution`, telling apart boards breaking the rules, boards without
+/// a solution and aborted searches
+pub fn solve(sudoku: Sudoku, lock: &AbortLock) -> Result<Sudoku, SolveError> {
+  checked_search(sudoku, lock, |propagated| Solver::new(lock).search(&propagated))
+}
+
+/// Checks around the search behind `solve`: boards breaking the rules are refused
+/// before `search` gets the propagated board, and only a solved board counts as its result
+fn checked_search(sudoku: Sudoku, lock: &AbortLock, search: impl FnOnce(Sudoku) -> Option<Sudoku>) 
+    -> Result<Sudoku, SolveError> {
+  let conflicts = sudoku.conflicts();
+  if !conflicts.is_empty() {
+    return Err(SolveError::InvalidBoard(conflicts));
+  }
+  match search(propagate(sudoku).0) {
+    Some(solved) if solved.is_solved() => Ok(solved),
+    _ if lock.is_aborted() => Err(SolveError::Aborted),
+    _ => Err(SolveError::Unsolvable)
+  }
+}
+
+/// Same as `solution`, but the whole search gives up with `None` once more than
+/// `max_depth` digits would have to be guessed on top of each other. Every guess
+/// fills one field, so `board_size²` is never exceeded.
+pub fn solution_with_max_depth(sudoku: Sudoku, max_depth: usize, lock: &AbortLock) -> Option<Sudoku> {
+  checked_search(sudoku, lock, |propagated| {
+    let mut search = Search::new(FillOrder::Lexicographic, lock);
+    search.max_depth = max_depth;
+    search.solve(propagated)
+  }).ok()
+}
+
+/// Like `solution`, but tells which clues are to blame when there is none: both
+/// fields of the first pair holding the same digit in a unit, otherwise clues seen by
+/// the first empty field left without candidates, otherwise clues of the first unit
+/// whose digits can't all be placed. Empty when the contradiction only shows up
+/// deeper in the search or the lock got aborted.
+pub fn solution_diagnostic(sudoku: Sudoku, lock: &AbortLock) -> Result<Sudoku, Vec<Position>> {
+  if let Some((first, second)) = sudoku.conflicts().into_iter().next() {
+    return Err(vec![first, second]);
+  }
+  if let Some(solved) = solution(sudoku.clone(), lock) {
+    return Ok(solved);
+  }
+
+  let clues_of = |fields: Vec<Position>| fields.into_iter()
+    .filter(|pos| sudoku.at(*pos) != 0)
+    .collect();
+  let blocked_field = sudoku.iter()
+    .find(|(digit, pos)| *digit == 0 && sudoku.available(*pos).is_empty());
+  if let Some((_, pos)) = blocked_field {
+    return Err(clues_of(sudoku.peers(pos)));
+  }
+  let broken_unit = sudoku.units().into_iter()
+    .find(|unit| !has_perfect_matching(unit.iter().map(|pos| sudoku.available(*pos)).collect()));
+  Err(broken_unit.map(clues_of).unwrap_or_default())
+}
+
+/// Completion found by branching on the field picked by given strategy. Unlike with
+/// `solution` it doesn't have to be the lexicographically smallest one.
+pub fn solution_with_strategy(sudoku: Sudoku, strategy: BranchStrategy, lock: &AbortLock) -> Option<Sudoku> {
+  let order = match strategy {
+    BranchStrategy::MinCandidates => FillOrder::MRV,
+    BranchStrategy::MinCandidatesThenLocality => FillOrder::LocalMRV,
+  };
+  Search::new(order, lock).solve(sudoku)
+}
+
+/// Solves every line of an imported batch, lines which can't be loaded or solved
+/// give `None`. Once the lock gets aborted all remaining entries are `None` too.
+pub fn solve_batch(lines: &[&str], box_size: u8, lock: &AbortLock) -> Vec<Option<Sudoku>> {
+  lines.iter()
+    .map(|line| match Sudoku::load_checked(line.trim_end_matches(['\r', '\n']), box_size) {
+      Ok(sudoku) if !lock.is_aborted() => solution(sudoku, lock),
+      _ => None
+    })
+    .collect()
+}
+
+/// Same as `solution`, but remembers perfect matching results across the search
+pub fn solution_cached(sudoku: Sudoku, lock: &AbortLock) -> Option<Sudoku> {
+  checked_search(sudoku, lock, |propagated| {
+    let mut search = Search::new(FillOrder::Lexicographic, lock);
+    search.state = Some(SolveState::new());
+    search.solve(propagated)
+  }).ok()
+}
+
+/// Same search as `solution`, but pending branches are kept on an explicit stack
+/// instead of the call stack, so hard or large boards can't overflow the WASM stack
+pub fn solution_nonrecursive(sudoku: Sudoku, lock: &AbortLock) -> Option<Sudoku> {
+  checked_search(sudoku, lock, |propagated| SolutionIter::new(propagated, lock, get_lexicographic_options).next())
+    .ok()
+}
+
+/// Solution `solution` gives along with the number of backtracks a plain brute force
+/// search takes to complete the board, a common measure of how hard the puzzle is for
+/// brute force. The count comes from branching with `FillOrder::PlainMRV`, as the look
+/// ahead of `solution` prunes so well that it hardly ever has to backtrack.
+pub fn solution_with_effort(sudoku: Sudoku, lock: &AbortLock) -> Option<(Sudoku, u64)> {
+  let mut search = Search::new(FillOrder::PlainMRV, lock);
+  search.solve(sudoku.clone())?;
+  let backtracks = search.stats.backtracks;
+  solution(sudoku, lock).map(|solved| (solved, backtracks))
+}
+
+/// Shape of the search `solution` makes for the board, `None` when there is no
+/// solution or the lock got aborted
+pub fn search_metrics(sudoku: Sudoku, lock: &AbortLock) -> Option<SearchMetrics> {
+  let mut search = Search::new(FillOrder::Lexicographic, lock);
+  search.solve(propagate(sudoku).0)?;
+  Some(SearchMetrics { nodes: search.stats.nodes, max_depth: search.deepest, guesses: search.guesses })
+}
+
+/// Solves sudoku branching in given order and reports how much work it took.
+/// With `FillOrder::RowMajor` the result is the lexicographically smallest completion
+/// of the board read row by row.
+pub fn solution_stats(sudoku: Sudoku, order: FillOrder, lock: &AbortLock) -> (Option<Sudoku>, SolveStats) {
+  let mut search = Search::new(order, lock);
+  let result = search.solve(sudoku);
+  (result, search.stats)
+}
+
+/// Like `solution_stats`, but gives up after visiting `node_limit` nodes
+#[cfg(feature = "bench")]
+pub(crate) fn solution_stats_limited(sudoku: Sudoku, order: FillOrder, node_limit: u64, lock: &AbortLock) 
+    -> (Option<Sudoku>, SolveStats) {
+  let mut search = Search::new(order, lock);
+  search.node_limit = node_limit;
+  let result = search.solve(sudoku);
+  (result, search.stats)
+}
+
+/// Field to branch on in given order. Once `check` sees the lock aborted the remaining
+/// candidates count as unsolvable, so the caller should look at `check.aborted`.
+fn next_options(sudoku: &Sudoku, order: FillOrder, limit: u8, check: &mut AbortCheck, 
+    state: Option<&mut SolveState>) -> Option<(Flags, (usize, usize))> {
+  match (order, state) {
+    (FillOrder::MRV, None) => 
+      get_best_options_by(sudoku, limit, |updated| check.tick() || is_unsolvable(updated)),
+    (FillOrder::MRV, Some(state)) => 
+      get_best_options_by(sudoku, limit, |updated| check.tick() || is_unsolvable_cached(updated, state)),
+    (FillOrder::Lexicographic, None) => 
+      get_lexicographic_options_by(sudoku, limit, |updated| check.tick() || is_unsolvable(updated)),
+    (FillOrder::Lexicographic, Some(state)) => 
+      get_lexicographic_options_by(sudoku, limit, |updated| check.tick() || is_unsolvable_cached(updated, state)),
+    (FillOrder::PlainMRV, _) => most_constrained_empty(sudoku).map(|(pos, flags)| (flags, pos)),
+    (FillOrder::LocalMRV, None) => 
+      get_local_options_by(sudoku, limit, |updated| check.tick() || is_unsolvable(updated)),
+    (FillOrder::LocalMRV, Some(state)) => 
+      get_local_options_by(sudoku, limit, |updated| check.tick() || is_unsolvable_cached(updated, state)),
+    (FillOrder::RowMajor, _) => (0 .. sudoku.board_size())
+      .flat_map(|y| (0 .. sudoku.board_size()).map(move |x| (x, y)))
+      .find(|pos| sudoku.at(*pos) == 0)
+      .map(|pos| (sudoku.available(pos), pos))
+  }
 }
 
-fn get_best_options(sudoku: &Sudoku) -> Option<(Flags, (usize, usize))> {
+/// Candidates of every empty field. When the best field has between 2 and `limit` of
+/// them, only candidates which don't make the board unsolvable right away are kept.
+fn checked_options(sudoku: &Sudoku, limit: u8, mut unsolvable: impl FnMut(&Sudoku) -> bool) 
+    -> Vec<(Flags, (usize, usize))> {
   let options: Vec<_> = sudoku.iter()
     .filter(|(d, _)| *d == 0)
     .map(|(_, pos)| (sudoku.available(pos), pos))
     .collect();
 
-  let min_option_pos = options.iter()
-    .min_by(|(f1, _), (f2, _)| f1.partial_cmp(f2).unwrap_or(Equal));
-
-  if min_option_pos.is_some_and(|(f, _)| f.size() > 1) {
-    options.iter()
+  let min_size = options.iter().map(|(flags, _)| flags.size()).min();
+  if min_size.is_some_and(|size| size > 1 && size <= limit) {
+    options.into_iter()
       .map(|(flags, pos)| {
-        let real_possibilities = flags.to_vec().into_iter()
+        let real_possibilities = flags.iter()
           .filter(|digit| {
-            let updated_sudoku = sudoku.clone().set(*pos, *digit);
-            !is_unsolvable(&updated_sudoku)
+            let updated_sudoku = sudoku.clone().set(pos, *digit);
+            !unsolvable(&updated_sudoku)
           })
           .collect();
-        (Flags::from_vec(real_possibilities), *pos)
-      }).min_by(|(f1, _), (f2, _)| f1.partial_cmp(f2).unwrap_or(Equal))
+        (Flags::from_vec(real_possibilities), pos)
+      })
+      .collect()
   } else {
-    min_option_pos.map(|x| *x)
+    options
+  }
+}
+
+/// Field with the fewest candidates which don't make the board unsolvable right away.
+/// When even the best field has more than `limit` candidates they are not checked.
+fn get_best_options_by(sudoku: &Sudoku, limit: u8, unsolvable: impl FnMut(&Sudoku) -> bool) 
+    -> Option<(Flags, (usize, usize))> {
+  checked_options(sudoku, limit, unsolvable).into_iter()
+    .min_by(|(f1, _), (f2, _)| f1.partial_cmp(f2).unwrap_or(Equal))
+}
+
+/// Same counts as `get_best_options_by`, ties are broken in favour of fields with the
+/// most filled peers and then by reading order
+fn get_local_options_by(sudoku: &Sudoku, limit: u8, unsolvable: impl FnMut(&Sudoku) -> bool) 
+    -> Option<(Flags, (usize, usize))> {
+  checked_options(sudoku, limit, unsolvable).into_iter()
+    .min_by_key(|(flags, (x, y))| {
+      let filled_peers = sudoku.peers((*x, *y)).into_iter().filter(|peer| sudoku.at(*peer) != 0).count();
+      (flags.size(), usize::MAX - filled_peers, *y, *x)
+    })
+}
+
+/// Field `get_best_options_by` picks when it is forced, otherwise the first empty field
+/// in reading order. A field whose digit is forced holds it in every completion, so
+/// depth first search over these options meets completions in lexicographic order.
+fn get_lexicographic_options(sudoku: &Sudoku) -> Option<(Flags, (usize, usize))> {
+  get_lexicographic_options_by(sudoku, LOOKAHEAD_MAX_CANDIDATES, is_unsolvable)
+}
+
+fn get_lexicographic_options_by(sudoku: &Sudoku, limit: u8, mut unsolvable: impl FnMut(&Sudoku) -> bool) 
+    -> Option<(Flags, (usize, usize))> {
+  let (flags, pos) = get_best_options_by(sudoku, limit, &mut unsolvable)?;
+  if flags.size() <= 1 {
+    return Some((flags, pos));
+  }
+  let first = (0 .. sudoku.board_size())
+    .flat_map(|y| (0 .. sudoku.board_size()).map(move |x| (x, y)))
+    .find(|pos| sudoku.at(*pos) == 0)?;
+  let real_possibilities = sudoku.available(first).iter()
+    .filter(|digit| !unsolvable(&sudoku.clone().set(first, *digit)))
+    .collect();
+  Some((Flags::from_vec(real_possibilities), first))
+}
+
+/// Empty field with the fewest candidates, ties are broken by reading order
+pub fn most_constrained_empty(sudoku: &Sudoku) -> Option<((usize, usize), Flags)> {
+  sudoku.iter()
+    .filter(|(d, _)| *d == 0)
+    .map(|(_, pos)| (pos, sudoku.available(pos)))
+    .min_by_key(|((x, y), flags)| (flags.size(), *y, *x))
+}
+
+/// Lazy depth first enumeration behind `solution_iter`. Pending branches are kept
+/// on an explicit stack, so the depth of the search doesn't nest iterators.
+struct SolutionIter<'r> {
+  lock: &'r AbortLock,
+  pick: fn(&Sudoku) -> Option<(Flags, Position)>,
+  start: Option<Sudoku>,
+  stack: Vec<(Sudoku, Position, Vec<u8>)>,
+}
+
+impl<'r> SolutionIter<'r> {
+  /// Starts at the board, `pick` chooses the field to branch on and its digits to try
+  fn new(sudoku: Sudoku, lock: &'r AbortLock, pick: fn(&Sudoku) -> Option<(Flags, Position)>) -> Self {
+    SolutionIter { lock, pick, start: Some(sudoku), stack: vec![] }
+  }
+
+  /// Next untried branch, dropping exhausted frames on the way
+  fn next_branch(&mut self) -> Option<Sudoku> {
+    loop {
+      let (board, pos, digits) = self.stack.last_mut()?;
+      match digits.pop() {
+        Some(digit) => return Some(board.clone().set(*pos, digit)),
+        None => { self.stack.pop(); }
+      }
+    }
+  }
+}
+
+impl<'r> Iterator for SolutionIter<'r> {
+  type Item = Sudoku;
+
+  fn next(&mut self) -> Option<Sudoku> {
+    let mut current = match self.start.take() {
+      Some(sudoku) => sudoku,
+      None => self.next_branch()?,
+    };
+    loop {
+      if self.lock.is_aborted() {
+        self.stack.clear();
+        return None;
+      }
+      match (self.pick)(&current) {
+        None if current.is_solved() => return Some(current),
+        None => current = self.next_branch()?,
+        Some((flags, pos)) => {
+          let digits = flags.to_vec().into_iter().rev().collect();
+          self.stack.push((current, pos, digits));
+          current = self.next_branch()?;
+        }
+      }
+    }
   }
 }
 
+/// Enumerates every solution in lexicographic order of the digits read row by row.
+/// Forced digits are propagated first and the search branches like `solution` does, so the
+/// first solution is always the one `solution` returns. Filled boards breaking a rule
+/// are not solutions, so nothing is given for them.
 pub fn solution_iter<'r> (sudoku: Sudoku, lock: &'r AbortLock) 
     -> Box<dyn Iterator<Item=Sudoku> + 'r> {
-  if lock.is_aborted() {
-    return Box::new(vec![].into_iter());
-  }
-  match get_best_options(&sudoku) {
-    None => Box::new(vec![sudoku].into_iter()),
-    Some((flags, pos)) => {
-      return Box::new(
-        flags.to_vec().into_iter()
-          .flat_map(move |digit| {
-            let updated_sudoku = sudoku.clone().set(pos, digit);
-            solution_iter(updated_sudoku, lock)
-          })
-        )
-    }
+  Box::new(SolutionIter::new(propagate(sudoku).0, lock, get_lexicographic_options))
+}
+
+/// Enumerates the same solutions as `solution_iter`, in a different order, as it branches
+/// on the field with the fewest candidates without checking every digit for direct
+/// contradictions first. Nodes are much cheaper while the search tree gets larger.
+pub fn solution_iter_fast<'r> (sudoku: Sudoku, lock: &'r AbortLock) 
+    -> Box<dyn Iterator<Item=Sudoku> + 'r> {
+  let pick = |sudoku: &Sudoku| most_constrained_empty(sudoku).map(|(pos, flags)| (flags, pos));
+  Box::new(SolutionIter::new(sudoku, lock, pick))
+}
+
+/// Passes every solution to `f` in the order of `solution_iter`, without building
+/// boxed iterators. Enumeration stops once `f` returns false.
+pub fn for_each_solution<F: FnMut(&Sudoku) -> bool>(sudoku: Sudoku, lock: &AbortLock, mut f: F) {
+  Search::new(FillOrder::Lexicographic, lock).visit_all(sudoku, &mut f);
+}
+
+/// Counts solutions, stopping once `limit` of them are found
+pub fn count_solutions(sudoku: Sudoku, limit: usize, lock: &AbortLock) -> usize {
+  solution_iter(sudoku, lock).take(limit).count()
+}
+
+/// Same as `count_solutions`, calling `progress` with the number of explored
+/// search nodes after every `every` of them, so long enumerations can show activity
+pub fn count_solutions_with_progress(sudoku: Sudoku, limit: usize, lock: &AbortLock, every: u64, 
+    mut progress: impl FnMut(u64)) -> usize {
+  if limit == 0 {
+    return 0;
+  }
+  let mut count = 0;
+  let mut search = Search::new(FillOrder::Lexicographic, lock);
+  search.progress = Some((every.max(1), &mut progress));
+  search.visit_all(sudoku, &mut |_| { count += 1; count < limit });
+  count
+}
+
+/// Collects solutions until `max_nodes` boards were tried, also tells whether the
+/// budget ran out before the search got through all of them
+pub fn solutions_within_budget(sudoku: Sudoku, max_nodes: u64, lock: &AbortLock) -> (Vec<Sudoku>, bool) {
+  let mut solutions = vec![];
+  let mut search = Search::new(FillOrder::Lexicographic, lock);
+  search.node_limit = max_nodes;
+  let completed = search.visit_all(sudoku, &mut |solved| { solutions.push(solved.clone()); true });
+  (solutions, !completed && search.stats.nodes >= max_nodes)
+}
+
+/// Checks whether the board can be completed at all, the search stops at the first
+/// solution found. Gives false once the lock gets aborted, as nothing was proven.
+pub fn is_solvable(sudoku: &Sudoku, lock: &AbortLock) -> bool {
+  solution(sudoku.clone(), lock).is_some() && !lock.is_aborted()
+}
+
+/// Checks whether the board can still be completed after the player writes the digit
+/// into the field. Moves breaking a rule right away are never solvable. For now the
+/// whole board is searched again, this is the place where an incremental check
+/// reusing earlier work would go.
+pub fn still_solvable_after(sudoku: &Sudoku, pos: Position, digit: u8, lock: &AbortLock) -> bool {
+  match sudoku.try_set(pos, digit) {
+    Ok(moved) => is_solvable(&moved, lock),
+    Err(_) => false
+  }
+}
+
+/// First solution along with whether it is the only one, both from a single search
+/// which stops as soon as a second solution shows up
+pub fn solve_and_check_unique(sudoku: Sudoku, lock: &AbortLock) -> (Option<Sudoku>, bool) {
+  let mut solutions = solution_iter(sudoku, lock);
+  let first = solutions.next();
+  let unique = first.is_some() && solutions.next().is_none() && !lock.is_aborted();
+  (first, unique)
+}
+
+pub fn has_unique_solution(sudoku: &Sudoku, lock: &AbortLock) -> bool {
+  count_solutions(sudoku.clone(), 2, lock) == 1 && !lock.is_aborted()
+}
+
+/// Checks that sudoku has a unique solution and none of its clues is redundant,
+/// so removing any of them gives a puzzle with more solutions
+pub fn is_minimal(sudoku: &Sudoku, lock: &AbortLock) -> bool {
+  if !has_unique_solution(sudoku, lock) {
+    return false;
   }
+
+  sudoku.iter()
+    .filter(|(d, _)| *d != 0)
+    .all(|(_, pos)| {
+      !lock.is_aborted() && count_solutions(sudoku.clone().set(pos, 0), 2, lock) > 1
+    }) && !lock.is_aborted()
 }
 
 /// Checks for direct problems in this sudoku
 pub fn is_unsolvable(sudoku: &Sudoku) -> bool {
-  let is_field_out_of_options = sudoku.iter()
-    .filter(|(d, _)| *d == 0)
-    .map(|(_, (x, y))| sudoku.used((x, y)).inverse(sudoku.board_size() as u8))
-    .min_by(|f1, f2| f1.partial_cmp(f2).unwrap_or(Equal))
-    .is_some_and(|f| f.size() < 1);
+  unsolvable_by(sudoku, has_perfect_matching)
+}
 
-  let row_without_solution = (0 .. sudoku.board_size())
-    .any(|y| !has_perfect_matching(sudoku.iter_row_avail(y).collect()));
+/// Same as `is_unsolvable`, reusing matching results remembered in the state
+pub fn is_unsolvable_cached(sudoku: &Sudoku, state: &mut SolveState) -> bool {
+  unsolvable_by(sudoku, |unit| state.has_perfect_matching(unit))
+}
 
-  let column_without_solution = (0 .. sudoku.board_size())
-    .any(|x| !has_perfect_matching(sudoku.iter_column_avail(x).collect()));
+/// Looks for an empty field without candidates first, matchings of the units are
+/// only computed when there is none and stop at the first failing unit
+fn unsolvable_by(sudoku: &Sudoku, mut matches: impl FnMut(Vec<Flags>) -> bool) -> bool {
+  let is_field_out_of_options = sudoku.iter()
+    .filter(|(d, _)| *d == 0)
+    .any(|(_, (x, y))| sudoku.used((x, y)).inverse(sudoku.board_size() as u8).is_empty());
+  if is_field_out_of_options {
+    return true;
+  }
 
-  let box_without_solution = (0 .. sudoku.box_size())
-    .flat_map(|x| (0 .. sudoku.box_size()).map(move |y| (x, y)))
-    .any(|pos| !has_perfect_matching(sudoku.iter_box_avail(pos).collect()));
+  let board_size = sudoku.board_size();
+  let mut boxes = (0 .. sudoku.box_size())
+    .flat_map(|x| (0 .. sudoku.box_size()).map(move |y| (x, y)));
 
-  is_field_out_of_options || row_without_solution || column_without_solution || box_without_solution
+  (0 .. board_size).any(|y| !matches(sudoku.iter_row_avail(y).collect()))
+    || (0 .. board_size).any(|x| !matches(sudoku.iter_column_avail(x).collect()))
+    || boxes.any(|pos| !matches(sudoku.iter_box_avail(pos).collect()))
+    || sudoku.windoku_regions().into_iter()
+      .any(|region| !matches(region.into_iter().map(|pos| sudoku.available(pos)).collect()))
+    || sudoku.cages().iter().any(|cage| sudoku.is_cage_broken(cage))
+    || sudoku.is_anti_knight() && sudoku.iter()
+      .any(|(digit, pos)| digit != 0 && sudoku.knight_peers(pos).into_iter().any(|peer| sudoku.at(peer) == digit))
 }
 
 pub fn contradiction(sudoku: &Sudoku, level: u8, lock: &AbortLock) -> bool {
@@ -97,7 +853,7 @@ pub fn contradiction(sudoku: &Sudoku, level: u8, lock: &AbortLock) -> bool {
 
   match level {
     0 => false,
-    1 => is_unsolvable(&sudoku),
+    1 => is_unsolvable(sudoku),
     _ => {
       let options: Vec<(Flags, (usize, usize))> = sudoku.iter()
         .filter(|(d, _)| *d == 0)
@@ -106,8 +862,8 @@ pub fn contradiction(sudoku: &Sudoku, level: u8, lock: &AbortLock) -> bool {
         .collect();
       
       options.iter().any(|(flags, (x, y))| {
-        flags.to_vec().iter().all(|digit| {
-          let updated_sudoku = sudoku.clone().set((*x, *y), *digit);
+        flags.iter().all(|digit| {
+          let updated_sudoku = sudoku.clone().set((*x, *y), digit);
           is_unsolvable(&updated_sudoku) || contradiction(&updated_sudoku, level - 1, lock)
         })
       })
@@ -115,7 +871,54 @@ pub fn contradiction(sudoku: &Sudoku, level: u8, lock: &AbortLock) -> bool {
   }
 }
 
+/// Smallest level at which `contradiction` proves the sudoku broken, `None` when no
+/// level up to `max_level` does. Found in a single search instead of trying the levels
+/// one by one: a field needs one level more than the deepest of its digits, and once
+/// some field proves a level the remaining ones are only searched for shallower proofs.
+pub fn contradiction_depth(sudoku: &Sudoku, max_level: u8, lock: &AbortLock) -> Option<u8> {
+  if max_level == 0 || lock.is_aborted() {
+    return None;
+  }
+  if is_unsolvable(sudoku) {
+    return Some(1);
+  }
+
+  let options: Vec<(Flags, (usize, usize))> = sudoku.iter()
+    .filter(|(d, _)| *d == 0)
+    .map(|(_, (x, y))| (sudoku.used((x, y)).inverse(sudoku.board_size() as u8), (x, y)))
+    .sorted_by(|(f1, _), (f2, _)| f1.partial_cmp(f2).unwrap_or(Equal))
+    .collect();
+
+  let mut best = None;
+  for (flags, pos) in options {
+    let bound = best.map_or(max_level, |level: u8| level - 1);
+    if bound < 2 {
+      break;
+    }
+    let level = flags.iter().try_fold(2, |level, digit| {
+      contradiction_depth(&sudoku.clone().set(pos, digit), bound - 1, lock)
+        .map(|depth| level.max(depth + 1))
+    });
+    best = level.or(best);
+  }
+  if lock.is_aborted() { None } else { best }
+}
+
 pub fn hint(sudoku: Sudoku, max_level: u8, lock: &AbortLock) 
+  -> Option<(u8, (usize, usize), u8)> {
+  let mut depths = HashMap::new();
+  hint_by(&sudoku, max_level, lock, |pos, digit, level| match level {
+    0 => false,
+    1 => is_unsolvable(&sudoku.clone().set(pos, digit)),
+    _ => depths.entry((pos, digit))
+      .or_insert_with(|| contradiction_depth(&sudoku.clone().set(pos, digit), max_level, lock))
+      .is_some_and(|depth| depth <= level)
+  })
+}
+
+/// Search of `hint` with the check whether writing a digit into a field leads to a
+/// contradiction at given level supplied by the caller
+fn hint_by(sudoku: &Sudoku, max_level: u8, lock: &AbortLock, mut contradicts: impl FnMut(Position, u8, u8) -> bool) 
   -> Option<(u8, (usize, usize), u8)> {
   let mut options: Vec<(Vec<u8>, (usize, usize))> = sudoku.iter()
     .filter(|(d, _)| *d == 0)
@@ -130,10 +933,8 @@ pub fn hint(sudoku: Sudoku, max_level: u8, lock: &AbortLock)
 
     options = options.into_iter().map(|(possibilities, (x, y))| {
       let real_possibilities: Vec<u8> = possibilities.into_iter()
-        .filter(|digit| {
-          let updated_sudoku = sudoku.clone().set((x, y), *digit);
-          !contradiction(&updated_sudoku, level, lock)
-        }).collect();
+        .filter(|digit| !contradicts((x, y), *digit, level))
+        .collect();
       (real_possibilities, (x, y))
     }).collect();
 
@@ -155,37 +956,159 @@ pub fn hint(sudoku: Sudoku, max_level: u8, lock: &AbortLock)
   None
 }
 
-#[cfg(test)]
-mod test {
-  use crate::solver::*;
-
-  macro_rules! time {
-    ($x:expr) => {{
-    let start = std::time::Instant::now();
-    let result = $x;
-    let duration = start.elapsed();
-    println!("Time taken: {:?}", duration);
-    result
-    }};
+/// Correct digit for an empty field taken from the unique solution, unlike `hint`
+/// the field doesn't have to be forced yet. Without `pos` the empty field with the
+/// fewest candidates is revealed, as that is where the player is most likely stuck.
+/// `None` for filled fields and for puzzles without exactly one solution.
+pub fn reveal_cell(sudoku: &Sudoku, pos: Option<Position>, lock: &AbortLock) -> Option<(Position, u8)> {
+  let pos = match pos {
+    Some(pos) if sudoku.at(pos) == 0 => pos,
+    Some(_) => return None,
+    None => most_constrained_empty(sudoku)?.0,
+  };
+  let mut solutions = solution_iter(sudoku.clone(), lock).take(2);
+  let solved = solutions.next()?;
+  if solutions.next().is_some() || lock.is_aborted() {
+    return None;
   }
+  Some((pos, solved.at(pos)))
+}
 
-  fn solve_by_hints(mut sudoku: Sudoku) -> Option<Sudoku> {
-    while let Some((d, pos, _)) = hint(sudoku.clone(), 2, &AbortLock::prepare()) {
-      sudoku = sudoku.set(pos, d)     
-    }
-    if sudoku.is_solved() {
-      Some(sudoku)
-    } else {
-      None
+/// Preview of the next moves: board after the first `steps` placements the search of
+/// `solution` makes, forced fields first and then the fields it branches on. Digits
+/// come from the solution found, so the preview never leads into a dead end. Fewer
+/// fields are filled when fewer are empty. `None` when there is no solution or the
+/// lock got aborted.
+pub fn solve_prefix(sudoku: Sudoku, steps: usize, lock: &AbortLock) -> Option<Sudoku> {
+  let solved = solution(sudoku.clone(), lock)?;
+  let mut board = sudoku;
+  for _ in 0 .. steps {
+    match get_lexicographic_options(&board) {
+      Some((_, pos)) => board.put(pos, solved.at(pos)),
+      None => break
     }
   }
+  Some(board)
+}
 
-  fn collect_all<'r, A>(iter: Box<dyn Iterator<Item = A> + 'r>) -> Vec<A> {
-    let mut res = vec![];
-    for i in iter {
-      res.push(i);
+/// Candidates of empty fields which appear in at least one solution, fields in reading
+/// order and digits ascending. Every other candidate can be eliminated. Each found
+/// solution confirms all of its digits at once, so only the remaining candidates
+/// need a search of their own. Empty once the lock gets aborted.
+pub fn viable_candidates(sudoku: &Sudoku, lock: &AbortLock) -> Vec<(Position, u8)> {
+  let n = sudoku.board_size();
+  let mut viable = vec![vec![Flags::empty(); n]; n];
+  let mut result = vec![];
+  for (x, y) in (0 .. n).flat_map(|y| (0 .. n).map(move |x| (x, y))) {
+    if sudoku.at((x, y)) != 0 {
+      continue;
     }
-    res
+    for digit in sudoku.available((x, y)).iter() {
+      if !viable[y][x].contains(digit) {
+        if let Some(solved) = solution(sudoku.clone().set((x, y), digit), lock) {
+          for (found, (sx, sy)) in solved.iter() {
+            viable[sy][sx] = viable[sy][sx].set(found);
+          }
+        }
+      }
+      if lock.is_aborted() {
+        return vec![];
+      }
+      if viable[y][x].contains(digit) {
+        result.push(((x, y), digit));
+      }
+    }
+  }
+  result
+}
+
+/// Gives the same hints as `hint` for a board which keeps getting filled in, like
+/// during an interactive session. A digit leading to a contradiction keeps doing so
+/// once more digits are written, so every contradiction found is remembered with its
+/// level and checked again only at lower levels. The memory is dropped whenever the
+/// board is not an extension of the previous one, e.g. after a digit got erased.
+pub struct HintEngine<'r> {
+  lock: &'r AbortLock,
+  max_level: u8,
+  board: Option<Sudoku>,
+  contradictions: HashMap<(Position, u8), u8>,
+}
+
+impl<'r> HintEngine<'r> {
+  pub fn new(max_level: u8, lock: &'r AbortLock) -> Self {
+    HintEngine { lock, max_level, board: None, contradictions: HashMap::new() }
+  }
+
+  /// Same result as `hint` with the level limit of the engine
+  pub fn next_hint(&mut self, sudoku: &Sudoku) -> Option<(u8, Position, u8)> {
+    let extends_previous = self.board.as_ref().is_some_and(|previous| {
+      previous.board_size() == sudoku.board_size()
+        && previous.iter().all(|(digit, pos)| digit == 0 || sudoku.at(pos) == digit)
+    });
+    if !extends_previous {
+      self.contradictions.clear();
+    }
+    self.board = Some(sudoku.clone());
+
+    let (contradictions, lock) = (&mut self.contradictions, self.lock);
+    hint_by(sudoku, self.max_level, lock, |pos, digit, level| {
+      if contradictions.get(&(pos, digit)).is_some_and(|known| *known <= level) {
+        return true;
+      }
+      let found = contradiction(&sudoku.clone().set(pos, digit), level, lock);
+      if found && !lock.is_aborted() {
+        contradictions.insert((pos, digit), level);
+      }
+      found
+    })
+  }
+}
+
+/// Fields holding different digits in the first two solutions found, empty when
+/// the sudoku has at most one solution. Tells the player why `hint` has nothing to offer.
+pub fn ambiguous_cells(sudoku: &Sudoku, lock: &AbortLock) -> Vec<(usize, usize)> {
+  let solutions: Vec<Sudoku> = solution_iter(sudoku.clone(), lock).take(2).collect();
+  if solutions.len() < 2 {
+    return vec![];
+  }
+
+  (0 .. sudoku.board_size())
+    .flat_map(|y| (0 .. sudoku.board_size()).map(move |x| (x, y)))
+    .filter(|pos| solutions[0].at(*pos) != solutions[1].at(*pos))
+    .collect()
+}
+
+#[cfg(test)]
+mod test {
+  use crate::solver::*;
+
+  macro_rules! time {
+    ($x:expr) => {{
+    let start = std::time::Instant::now();
+    let result = $x;
+    let duration = start.elapsed();
+    println!("Time taken: {:?}", duration);
+    result
+    }};
+  }
+
+  fn solve_by_hints(mut sudoku: Sudoku) -> Option<Sudoku> {
+    while let Some((d, pos, _)) = hint(sudoku.clone(), 2, &AbortLock::prepare()) {
+      sudoku = sudoku.set(pos, d)     
+    }
+    if sudoku.is_solved() {
+      Some(sudoku)
+    } else {
+      None
+    }
+  }
+
+  fn collect_all<'r, A>(iter: Box<dyn Iterator<Item = A> + 'r>) -> Vec<A> {
+    let mut res = vec![];
+    for i in iter {
+      res.push(i);
+    }
+    res
   }
   
   #[test]
@@ -200,6 +1123,532 @@ mod test {
     assert!(solution.is_some_and(|x| x.is_solved()));
   }
 
+  #[test]
+  fn trivial_solution_row_major() {
+    let data 
+      = "   3".to_string()
+      + "3  2"
+      + "2  1"
+      + "1   ";
+    let sudoku = Sudoku::load(data.as_str(), 2);
+    let (solution, stats) = solution_stats(sudoku, FillOrder::RowMajor, &AbortLock::prepare());
+    assert!(solution.is_some_and(|x| x.is_solved()));
+    assert!(stats.nodes >= 10);
+  }
+
+  #[test]
+  fn row_major_gives_smallest_solution() {
+    let data 
+      = "1   ".to_string()
+      + "    "
+      + "    "
+      + "    ";
+    let sudoku = Sudoku::load(data.as_str(), 2);
+    let lock = AbortLock::prepare();
+    let smallest = collect_all(solution_iter(sudoku.clone(), &lock)).into_iter()
+      .min_by_key(|s| s.save());
+    let (row_major, _) = solution_stats(sudoku.clone(), FillOrder::RowMajor, &lock);
+    assert_eq!(Some("1234341221434321".to_string()), row_major.clone().map(|s| s.save()));
+    assert_eq!(smallest, row_major);
+    assert_eq!(smallest, solution(sudoku, &lock));
+  }
+
+  #[test]
+  fn solutions_in_lexicographic_order() {
+    let lock = AbortLock::prepare();
+    let sudoku = Sudoku::load(" 4      3    1  ", 2);
+    let expected = vec![
+      "1423231432414132", "1423234132144132", "1432231432414123",
+      "1432234132144123", "2413132432414132", "2431134232144123",
+    ];
+    let found: Vec<String> = solution_iter(sudoku.clone(), &lock).map(|s| s.save()).collect();
+    assert_eq!(expected, found);
+    let mut visited = vec![];
+    for_each_solution(sudoku, &lock, |s| { visited.push(s.save()); true });
+    assert_eq!(expected, visited);
+
+    let saves: Vec<String> = solution_iter(Sudoku::empty(2).set((1, 1), 3), &lock).map(|s| s.save()).collect();
+    assert!(saves.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(72, saves.len());
+  }
+
+  #[test]
+  fn for_each_solution_stops() {
+    let data 
+      = "1   ".to_string()
+      + "    "
+      + "    "
+      + "    ";
+    let sudoku = Sudoku::load(data.as_str(), 2);
+    let lock = AbortLock::prepare();
+
+    let mut visited = vec![];
+    for_each_solution(sudoku.clone(), &lock, |s| { visited.push(s.clone()); false });
+    assert_eq!(1, visited.len());
+    assert_eq!(solution_iter(sudoku.clone(), &lock).next(), visited.pop());
+
+    let mut count = 0;
+    for_each_solution(sudoku.clone(), &lock, |_| { count += 1; true });
+    assert_eq!(collect_all(solution_iter(sudoku, &lock)).len(), count);
+  }
+
+  #[test]
+  fn solution_depth_guard() {
+    let lock = AbortLock::prepare();
+    let empty = Sudoku::empty(3);
+    assert_eq!(None, solution_with_max_depth(empty.clone(), 40, &lock));
+    assert!(solution_with_max_depth(empty.clone(), 81, &lock).is_some_and(|s| s.is_solved()));
+    assert!(solution(empty, &lock).is_some_and(|s| s.is_solved()));
+    assert_eq!(None, solution_with_max_depth(Sudoku::load("1111111111111111", 2), 16, &lock));
+  }
+
+  #[test]
+  fn nonrecursive_matches_recursive() {
+    let trivial 
+      = "   3".to_string()
+      + "3  2"
+      + "2  1"
+      + "1   ";
+    let minimal 
+      = "".to_string()
+      + "   " + "   " + " 1 "
+      + "   " + "  2" + "  3"
+      + "   " + "4  " + "   "
+
+      + "   " + "   " + "5  "
+      + "4 1" + "6  " + "   "
+      + "  7" + "1  " + "   "
+
+      + " 5 " + "   " + "2  "
+      + "   " + " 8 " + " 4 "
+      + " 3 " + "91 " + "   ";
+    let corrupted 
+      = "".to_string()
+      + "9  " + "   " + "   "
+      + " 9 " + " 1 " + " 3 "
+      + "  6" + " 2 " + "7  "
+
+      + "   " + "3 4" + "   "
+      + "21 " + "   " + " 98"
+      + "   " + "   " + "   "
+
+      + "  2" + "5 6" + "4  "
+      + " 8 " + "   " + " 1 "
+      + "   " + "   " + "   ";
+    let medium 
+      = "".to_string()
+      + "  8" + "  3" + "461"
+      + "2 6" + " 84" + "   "
+      + "3  " + "  7" + " 9 "
+
+      + " 3 " + "75 " + "68 "
+      + " 87" + " 1 " + "   "
+      + " 5 " + " 4 " + "13 "
+
+      + "  9" + "27 " + "31 "
+      + "   " + "   " + "   "
+      + "763" + "4 1" + "8 2";
+    let hard 
+      = "".to_string()
+      + "3  " + " 2 " + "   "
+      + "  7" + "6  " + "  3"
+      + "6  " + "9  " + "  4"
+
+      + "2  " + "1  " + "6  "
+      + "9  " + "8 3" + " 5 "
+      + "  5" + "4  " + "7  "
+
+      + " 8 " + "  9" + " 1 "
+      + "   " + " 7 " + "2  "
+      + " 4 " + "  6" + "3  ";
+    let hard_for_brute_force 
+      = "".to_string()
+      + "   " + "   " + "   "
+      + "   " + "  3" + " 85"
+      + "  1" + " 2 " + "   "
+
+      + "   " + "5 7" + "   "
+      + "  4" + "   " + "1  "
+      + " 9 " + "   " + "   "
+
+      + "5  " + "   " + " 73"
+      + "  2" + " 1 " + "   "
+      + "   " + " 4 " + "  9";
+    let hardest_for_logic 
+      = "".to_string()
+      + "8  " + "   " + "   "
+      + "  3" + "6  " + "   "
+      + " 7 " + "  9" + "2  "
+
+      + " 5 " + "  7" + "   "
+      + "   " + " 45" + "7  "
+      + "   " + "1  " + " 3 "
+
+      + "  1" + "   " + " 68"
+      + "  8" + "5  " + " 1 "
+      + " 9 " + "   " + "4  ";
+    let non_unique 
+      = "".to_string()
+      + "   " + "   " + "   "
+      + " 9 " + " 1 " + " 3 "
+      + "  6" + " 2 " + "7  "
+
+      + "   " + "3 4" + "   "
+      + "21 " + "   " + " 98"
+      + "   " + "   " + "   "
+
+      + "  2" + "5 6" + "   "
+      + " 8 " + "   " + "   "
+      + "   " + "   " + "   ";
+    let lock = AbortLock::prepare();
+    let boards = vec![
+      Sudoku::load(trivial.as_str(), 2),
+      Sudoku::empty(2).set((0, 0), 1),
+      Sudoku::load(minimal.as_str(), 3),
+      Sudoku::load(corrupted.as_str(), 3),
+      Sudoku::load(medium.as_str(), 3),
+      Sudoku::load(hard.as_str(), 3),
+      Sudoku::load(hard_for_brute_force.as_str(), 3),
+      Sudoku::load(hardest_for_logic.as_str(), 3),
+      Sudoku::load(non_unique.as_str(), 3),
+      Sudoku::load("1111111111111111", 2),
+    ];
+
+    for sudoku in boards {
+      assert_eq!(solution(sudoku.clone(), &lock), solution_nonrecursive(sudoku, &lock));
+    }
+  }
+
+  #[test]
+  fn first_of_solution_iter_matches_solution() {
+    let non_unique 
+      = "".to_string()
+      + "   " + "   " + "   "
+      + " 9 " + " 1 " + " 3 "
+      + "  6" + " 2 " + "7  "
+
+      + "   " + "3 4" + "   "
+      + "21 " + "   " + " 98"
+      + "   " + "   " + "   "
+
+      + "  2" + "5 6" + "   "
+      + " 8 " + "   " + "   "
+      + "   " + "   " + "   ";
+    let lock = AbortLock::prepare();
+    let boards = vec![
+      Sudoku::load("   33  22  11   ", 2),
+      Sudoku::empty(2).set((1, 1), 3),
+      Sudoku::load("  123   4       ", 2),
+      Sudoku::load(non_unique.as_str(), 3),
+      Sudoku::empty(3).set((4, 4), 5),
+    ];
+
+    for sudoku in boards {
+      assert_eq!(solution(sudoku.clone(), &lock), solution_iter(sudoku, &lock).next());
+    }
+  }
+
+  #[test]
+  fn field_without_candidates_skips_matching() {
+    let sudoku = Sudoku::load("  123   4       ", 2);
+    let mut computed = 0;
+    assert!(unsolvable_by(&sudoku, |unit| { computed += 1; has_perfect_matching(unit) }));
+    assert_eq!(0, computed);
+    assert!(is_unsolvable(&sudoku));
+
+    assert!(!unsolvable_by(&Sudoku::empty(2), |unit| { computed += 1; has_perfect_matching(unit) }));
+    assert_eq!(12, computed);
+  }
+
+  #[test]
+  fn abort_during_search_stops_solver() {
+    let data 
+      = "".to_string()
+      + "8  " + "   " + "   "
+      + "  3" + "6  " + "   "
+      + " 7 " + "  9" + "2  "
+
+      + " 5 " + "  7" + "   "
+      + "   " + " 45" + "7  "
+      + "   " + "1  " + " 3 "
+
+      + "  1" + "   " + " 68"
+      + "  8" + "5  " + " 1 "
+      + " 9 " + "   " + "4  ";
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    let never = AbortLock::prepare();
+    let mut solver = Solver::new(&never);
+    assert!(solver.solve(&sudoku).is_some());
+    assert!(solver.checks() > 1000);
+
+    let lock = AbortLock::prepare();
+    let mut abort_at = |checks: u64| if checks == 1000 { lock.abort() };
+    let mut solver = Solver::new(&lock).with_check_hook(&mut abort_at);
+    assert_eq!(None, solver.solve(&sudoku));
+    assert!(solver.checks() <= 1000 + ABORT_CHECK_INTERVAL);
+
+    let mut abort_at = |checks: u64| if checks == 1000 { lock.abort() };
+    lock.reset();
+    let mut solver = Solver::new(&lock).with_abort_interval(1).with_check_hook(&mut abort_at);
+    assert_eq!(None, solver.solve(&sudoku));
+    assert_eq!(1000, solver.checks());
+
+    assert_eq!(None, solver.solve(&sudoku));
+    assert_eq!(0, solver.nodes());
+  }
+
+  #[test]
+  fn reused_solver_matches_solution() {
+    let minimal 
+      = "".to_string()
+      + "   " + "   " + " 1 "
+      + "   " + "  2" + "  3"
+      + "   " + "4  " + "   "
+
+      + "   " + "   " + "5  "
+      + "4 1" + "6  " + "   "
+      + "  7" + "1  " + "   "
+
+      + " 5 " + "   " + "2  "
+      + "   " + " 8 " + " 4 "
+      + " 3 " + "91 " + "   ";
+    let medium 
+      = "".to_string()
+      + "  8" + "  3" + "461"
+      + "2 6" + " 84" + "   "
+      + "3  " + "  7" + " 9 "
+
+      + " 3 " + "75 " + "68 "
+      + " 87" + " 1 " + "   "
+      + " 5 " + " 4 " + "13 "
+
+      + "  9" + "27 " + "31 "
+      + "   " + "   " + "   "
+      + "763" + "4 1" + "8 2";
+    let hard 
+      = "".to_string()
+      + "3  " + " 2 " + "   "
+      + "  7" + "6  " + "  3"
+      + "6  " + "9  " + "  4"
+
+      + "2  " + "1  " + "6  "
+      + "9  " + "8 3" + " 5 "
+      + "  5" + "4  " + "7  "
+
+      + " 8 " + "  9" + " 1 "
+      + "   " + " 7 " + "2  "
+      + " 4 " + "  6" + "3  ";
+    let hard_for_brute_force 
+      = "".to_string()
+      + "   " + "   " + "   "
+      + "   " + "  3" + " 85"
+      + "  1" + " 2 " + "   "
+
+      + "   " + "5 7" + "   "
+      + "  4" + "   " + "1  "
+      + " 9 " + "   " + "   "
+
+      + "5  " + "   " + " 73"
+      + "  2" + " 1 " + "   "
+      + "   " + " 4 " + "  9";
+    let hardest_for_logic 
+      = "".to_string()
+      + "8  " + "   " + "   "
+      + "  3" + "6  " + "   "
+      + " 7 " + "  9" + "2  "
+
+      + " 5 " + "  7" + "   "
+      + "   " + " 45" + "7  "
+      + "   " + "1  " + " 3 "
+
+      + "  1" + "   " + " 68"
+      + "  8" + "5  " + " 1 "
+      + " 9 " + "   " + "4  ";
+    let non_unique 
+      = "".to_string()
+      + "   " + "   " + "   "
+      + " 9 " + " 1 " + " 3 "
+      + "  6" + " 2 " + "7  "
+
+      + "   " + "3 4" + "   "
+      + "21 " + "   " + " 98"
+      + "   " + "   " + "   "
+
+      + "  2" + "5 6" + "   "
+      + " 8 " + "   " + "   "
+      + "   " + "   " + "   ";
+    let corrupted 
+      = "".to_string()
+      + "9  " + "   " + "   "
+      + " 9 " + " 1 " + " 3 "
+      + "  6" + " 2 " + "7  "
+
+      + "   " + "3 4" + "   "
+      + "21 " + "   " + " 98"
+      + "   " + "   " + "   "
+
+      + "  2" + "5 6" + "4  "
+      + " 8 " + "   " + " 1 "
+      + "   " + "   " + "   ";
+    let cages = vec![
+      Cage { cells: vec![(0, 0), (1, 0)], sum: 3 }, Cage { cells: vec![(2, 0), (2, 1)], sum: 4 },
+      Cage { cells: vec![(0, 2), (0, 3)], sum: 6 }, Cage { cells: vec![(2, 3), (3, 3)], sum: 3 },
+    ];
+    let lock = AbortLock::prepare();
+    let boards = vec![
+      Sudoku::load(medium.as_str(), 3),
+      Sudoku::empty(2).set((0, 0), 1),
+      Sudoku::load(minimal.as_str(), 3),
+      Sudoku::load("11              ", 2),
+      Sudoku::empty(2).with_cages(cages),
+      Sudoku::load(medium.as_str(), 3).set((0, 0), 0),
+      Sudoku::load(hard.as_str(), 3),
+      Sudoku::load(hard_for_brute_force.as_str(), 3),
+      Sudoku::load(hardest_for_logic.as_str(), 3),
+      Sudoku::load(non_unique.as_str(), 3),
+      Sudoku::load(corrupted.as_str(), 3),
+      Sudoku::load("   33  22  11   ", 2),
+      Sudoku::load("1111111111111111", 2),
+    ];
+
+    let mut solver = Solver::new(&lock);
+    for sudoku in boards {
+      assert_eq!(solution(sudoku.clone(), &lock), solver.solve(&sudoku));
+    }
+  }
+
+  #[test]
+  fn fast_iter_same_solutions() {
+    let data 
+      = "".to_string()
+      + "  8" + "  3" + "461"
+      + "2 6" + " 84" + "   "
+      + "3  " + "  7" + " 9 "
+
+      + " 3 " + "75 " + "68 "
+      + " 87" + " 1 " + "   "
+      + " 5 " + " 4 " + "13 "
+
+      + "  9" + "27 " + "31 "
+      + "   " + "   " + "   "
+      + "763" + "4 1" + "8 2";
+    let lock = AbortLock::prepare();
+
+    for sudoku in [Sudoku::empty(2).set((0, 0), 1), Sudoku::load(data.as_str(), 3)] {
+      let all = collect_all(solution_iter(sudoku.clone(), &lock)).into_iter()
+        .map(|s| s.save())
+        .sorted()
+        .collect::<Vec<_>>();
+      let fast = collect_all(solution_iter_fast(sudoku, &lock)).into_iter()
+        .map(|s| s.save())
+        .sorted()
+        .collect::<Vec<_>>();
+      assert!(!all.is_empty());
+      assert_eq!(all, fast);
+    }
+    assert_eq!(0, solution_iter_fast(Sudoku::load("1111111111111111", 2), &lock).count());
+  }
+
+  #[test]
+  fn aborted_lock_short_circuits() {
+    let data 
+      = "   3".to_string()
+      + "3  2"
+      + "2  1"
+      + "1   ";
+    let sudoku = Sudoku::load(data.as_str(), 2);
+    let broken = sudoku.clone().set((1, 0), 3);
+    let lock = AbortLock::aborted();
+
+    assert!(contradiction(&broken, 2, &AbortLock::never()));
+    assert_eq!(None, solution(sudoku.clone(), &lock));
+    assert_eq!(None, solution_iter(sudoku.clone(), &lock).next());
+    assert_eq!(None, hint(sudoku, 2, &lock));
+    assert!(!contradiction(&broken, 2, &lock));
+  }
+
+  #[test]
+  fn first_solutions_of_almost_empty_board() {
+    let sudoku = Sudoku::empty(3).set((4, 4), 5);
+    let solutions = time!(collect_all(Box::new(solution_iter(sudoku, &AbortLock::prepare()).take(5))));
+    assert_eq!(5, solutions.len());
+    assert!(solutions.iter().all(|s| s.is_solved() && s.at((4, 4)) == 5));
+    assert_eq!(5, solutions.iter().map(|s| s.save()).unique().count());
+  }
+
+  #[test]
+  fn solutions_budget() {
+    let data 
+      = "".to_string()
+      + "  8" + "  3" + "461"
+      + "2 6" + " 84" + "   "
+      + "3  " + "  7" + " 9 "
+
+      + " 3 " + "75 " + "68 "
+      + " 87" + " 1 " + "   "
+      + " 5 " + " 4 " + "13 "
+
+      + "  9" + "27 " + "31 "
+      + "   " + "   " + "   "
+      + "763" + "4 1" + "8 2";
+    let lock = AbortLock::prepare();
+    let sudoku = Sudoku::load(data.as_str(), 3);
+
+    let (partial, exhausted) = solutions_within_budget(sudoku.clone(), 5, &lock);
+    assert!(partial.is_empty());
+    assert!(exhausted);
+
+    let (complete, exhausted) = solutions_within_budget(sudoku.clone(), 10_000, &lock);
+    assert_eq!(vec![solution(sudoku, &lock).unwrap()], complete);
+    assert!(!exhausted);
+
+    let (some, exhausted) = solutions_within_budget(Sudoku::empty(2), 40, &lock);
+    assert!(!some.is_empty() && some.len() < 288);
+    assert!(exhausted);
+  }
+
+  #[test]
+  fn solve_with_uniqueness() {
+    let lock = AbortLock::prepare();
+    let unique = Sudoku::load(" 2343 1221 3432 ", 2);
+    let (solved, is_unique) = solve_and_check_unique(unique.clone(), &lock);
+    assert!(is_unique);
+    assert_eq!(solution(unique, &lock), solved);
+
+    let (solved, is_unique) = solve_and_check_unique(Sudoku::empty(2), &lock);
+    assert!(!is_unique);
+    assert!(solved.is_some_and(|s| s.is_solved()));
+
+    assert_eq!((None, false), solve_and_check_unique(Sudoku::load("11              ", 2), &lock));
+  }
+
+  #[test]
+  fn batch_of_two() {
+    let medium 
+      = "".to_string()
+      + "  8" + "  3" + "461"
+      + "2 6" + " 84" + "   "
+      + "3  " + "  7" + " 9 "
+
+      + " 3 " + "75 " + "68 "
+      + " 87" + " 1 " + "   "
+      + " 5 " + " 4 " + "13 "
+
+      + "  9" + "27 " + "31 "
+      + "   " + "   " + "   "
+      + "763" + "4 1" + "8 2";
+    let corrupted = "99".to_string() + &" ".repeat(79);
+    let lines = [medium.as_str(), corrupted.as_str(), "12"];
+    let lock = AbortLock::prepare();
+
+    let solved = solve_batch(&lines, 3, &lock);
+    assert_eq!(3, solved.len());
+    assert!(solved[0].as_ref().is_some_and(|s| s.is_solved()));
+    assert_eq!(None, solved[1]);
+    assert_eq!(None, solved[2]);
+    assert!(solve_batch(&lines, 3, &AbortLock::aborted()).iter().all(|s| s.is_none()));
+  }
+
   #[test]
   fn trivial_solution_by_hints() {
     let data 
@@ -212,6 +1661,26 @@ mod test {
     assert!(solution.is_some_and(|x| x.is_solved()));
   }
 
+  #[test]
+  fn trivial_solution_by_hint_engine() {
+    let data 
+      = "   3".to_string()
+      + "3  2"
+      + "2  1"
+      + "1   ";
+    let lock = AbortLock::prepare();
+    let mut engine = HintEngine::new(2, &lock);
+    let mut stateless = Sudoku::load(data.as_str(), 2);
+    let mut sudoku = stateless.clone();
+    while let Some((digit, pos, level)) = engine.next_hint(&sudoku) {
+      assert_eq!(Some((digit, pos, level)), hint(stateless.clone(), 2, &lock));
+      sudoku = sudoku.set(pos, digit);
+      stateless = stateless.set(pos, digit);
+    }
+    assert_eq!(None, hint(stateless, 2, &lock));
+    assert!(sudoku.is_solved());
+  }
+
   #[test]
   fn minimal_solution() {
     let data 
@@ -220,22 +1689,221 @@ mod test {
       + "   " + "  2" + "  3"
       + "   " + "4  " + "   "
 
-      + "   " + "   " + "5  "
-      + "4 1" + "6  " + "   "
-      + "  7" + "1  " + "   "
+      + "   " + "   " + "5  "
+      + "4 1" + "6  " + "   "
+      + "  7" + "1  " + "   "
+
+      + " 5 " + "   " + "2  "
+      + "   " + " 8 " + " 4 "
+      + " 3 " + "91 " + "   ";
+    
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    let solution = time!(solution(sudoku, &AbortLock::prepare()));
+    assert!(solution.is_some_and(|x| x.is_solved()));
+  }
+
+  #[test]
+  fn solve_errors() {
+    let lock = AbortLock::prepare();
+    let solved = solve(Sudoku::load("   33  22  11   ", 2), &lock);
+    assert!(solved.is_ok_and(|s| s.is_solved()));
+    assert_eq!(Err(SolveError::Unsolvable), solve(Sudoku::load("  123   4       ", 2), &lock));
+    assert_eq!(Err(SolveError::Aborted), solve(Sudoku::empty(3), &AbortLock::aborted()));
+    assert_eq!(Err(SolveError::InvalidBoard(vec![((0, 0), (2, 0))])),
+      solve(Sudoku::empty(2).set((0, 0), 1).set((2, 0), 1), &lock));
+    assert_eq!(None, solution(Sudoku::load("1234341221434312", 2), &lock));
+  }
+
+  #[test]
+  fn preview_of_next_moves() {
+    let minimal 
+      = "".to_string()
+      + "   " + "   " + " 1 "
+      + "   " + "  2" + "  3"
+      + "   " + "4  " + "   "
+
+      + "   " + "   " + "5  "
+      + "4 1" + "6  " + "   "
+      + "  7" + "1  " + "   "
+
+      + " 5 " + "   " + "2  "
+      + "   " + " 8 " + " 4 "
+      + " 3 " + "91 " + "   ";
+    let lock = AbortLock::prepare();
+    let sudoku = Sudoku::load(minimal.as_str(), 3);
+
+    let preview = solve_prefix(sudoku.clone(), 3, &lock).unwrap();
+    assert_eq!(3, sudoku.differing_cells(&preview).len());
+    assert!(sudoku.differing_cells(&preview).iter().all(|pos| sudoku.at(*pos) == 0));
+    assert!(is_solvable(&preview, &lock));
+    assert_eq!(solution(sudoku.clone(), &lock), solution(preview, &lock));
+
+    assert_eq!(solution(sudoku.clone(), &lock), solve_prefix(sudoku, 1000, &lock));
+    assert_eq!(None, solve_prefix(Sudoku::load("  123   4       ", 2), 3, &lock));
+  }
+
+  #[test]
+  fn minimal_reveal_cell() {
+    let data 
+      = "".to_string()
+      + "   " + "   " + " 1 "
+      + "   " + "  2" + "  3"
+      + "   " + "4  " + "   "
+
+      + "   " + "   " + "5  "
+      + "4 1" + "6  " + "   "
+      + "  7" + "1  " + "   "
+
+      + " 5 " + "   " + "2  "
+      + "   " + " 8 " + " 4 "
+      + " 3 " + "91 " + "   ";
+
+    let lock = AbortLock::prepare();
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    let solved = solution(sudoku.clone(), &lock).unwrap();
+    assert_eq!(Some(((0, 0), solved.at((0, 0)))), reveal_cell(&sudoku, Some((0, 0)), &lock));
+    assert_eq!(None, reveal_cell(&sudoku, Some((7, 0)), &lock));
+
+    let (pos, digit) = reveal_cell(&sudoku, None, &lock).unwrap();
+    assert_eq!(0, sudoku.at(pos));
+    assert_eq!(solved.at(pos), digit);
+    assert_eq!(None, reveal_cell(&Sudoku::empty(2), None, &lock));
+  }
+
+  #[test]
+  fn minimal_all_solutions() {
+    let data 
+      = "".to_string()
+      + "   " + "   " + " 1 "
+      + "   " + "  2" + "  3"
+      + "   " + "4  " + "   "
+
+      + "   " + "   " + "5  "
+      + "4 1" + "6  " + "   "
+      + "  7" + "1  " + "   "
+
+      + " 5 " + "   " + "2  "
+      + "   " + " 8 " + " 4 "
+      + " 3 " + "91 " + "   ";
+  
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    let solutions = time!(collect_all(solution_iter(sudoku, &AbortLock::prepare())));
+    assert!(solutions.len() == 1);
+    assert!(solutions[0].is_solved());
+  }
+
+  #[test]
+  fn minimal_clues_check() {
+    let data 
+      = "1  4".to_string()
+      + "    "
+      + "    "
+      + " 32 ";
+    let lock = AbortLock::prepare();
+    let sudoku = Sudoku::load(data.as_str(), 2);
+    let redundant = sudoku.clone().set((1, 0), 2);
+
+    assert!(is_minimal(&sudoku, &lock));
+    assert!(!is_minimal(&redundant, &lock));
+  }
+
+  #[test]
+  fn killer_solution() {
+    let cage = |cells: Vec<(usize, usize)>, sum: u32| Cage { cells, sum };
+    let cages = vec![
+      cage(vec![(0, 0), (1, 0)], 3), cage(vec![(2, 0), (2, 1)], 4),
+      cage(vec![(3, 0), (3, 1)], 6), cage(vec![(0, 1), (1, 1)], 7),
+      cage(vec![(0, 2), (0, 3)], 6), cage(vec![(1, 2), (1, 3)], 4),
+      cage(vec![(2, 2), (3, 2)], 7), cage(vec![(2, 3), (3, 3)], 3),
+    ];
+    let lock = AbortLock::prepare();
+    let sudoku = Sudoku::empty(2).with_cages(cages);
+
+    assert!(count_solutions(Sudoku::empty(2), 2, &lock) > 1);
+    assert_eq!(1, count_solutions(sudoku.clone(), 2, &lock));
+    let solved = solution(sudoku, &lock).unwrap();
+    assert!(solved.is_solved());
+    assert_eq!("1234341221434321", solved.save());
+  }
+
+  #[test]
+  fn filled_board_breaking_rules_has_no_solution() {
+    let lock = AbortLock::prepare();
+    let broken = Sudoku::load("1111111111111111", 2);
+    assert_eq!(0, count_solutions(broken.clone(), 10, &lock));
+    assert_eq!(0, count_solutions_with_progress(broken.clone(), 10, &lock, 1, |_| {}));
+    assert!(!has_unique_solution(&broken, &lock));
+    assert!(!is_minimal(&broken, &lock));
+    assert_eq!((None, false), solve_and_check_unique(broken, &lock));
+
+    let solved = Sudoku::load("1234341221434321", 2);
+    assert_eq!(1, count_solutions(solved.clone(), 10, &lock));
+    assert!(has_unique_solution(&solved, &lock));
+  }
+
+  #[test]
+  fn another_hard_sudoku_solution() {
+    let data 
+      = "".to_string()
+      + "   " + "   " + "   "
+      + " 9 " + " 1 " + " 3 "
+      + "  6" + " 2 " + "7  "
+
+      + "   " + "3 4" + "   "
+      + "21 " + "   " + " 98"
+      + "   " + "   " + "   "
+
+      + "  2" + "5 6" + "4  "
+      + " 8 " + "   " + " 1 "
+      + "   " + "   " + "   ";
+    
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    let solution = time!(solution(sudoku, &AbortLock::prepare()));
+    assert!(solution.is_some_and(|x| x.is_solved()));
+  }
+
+  #[test]
+  fn corrupted_sudoku_solution() {
+    let data 
+      = "".to_string()
+      + "9  " + "   " + "   "
+      + " 9 " + " 1 " + " 3 "
+      + "  6" + " 2 " + "7  "
+
+      + "   " + "3 4" + "   "
+      + "21 " + "   " + " 98"
+      + "   " + "   " + "   "
 
-      + " 5 " + "   " + "2  "
-      + "   " + " 8 " + " 4 "
-      + " 3 " + "91 " + "   ";
+      + "  2" + "5 6" + "4  "
+      + " 8 " + "   " + " 1 "
+      + "   " + "   " + "   ";
     
     let sudoku = Sudoku::load(data.as_str(), 3);
     let solution = time!(solution(sudoku, &AbortLock::prepare()));
-    assert!(solution.is_some_and(|x| x.is_solved()));
+    assert!(solution.is_none());
   }
 
   #[test]
-  fn minimal_all_solutions() {
-    let data 
+  fn viable_candidates_match_enumeration() {
+    let lock = AbortLock::prepare();
+    for data in ["1     3        4", "12              ", "11              "] {
+      let sudoku = Sudoku::load(data, 2);
+      let expected: Vec<(Position, u8)> = solution_iter(sudoku.clone(), &lock)
+        .flat_map(|solved| solved.iter().collect::<Vec<_>>())
+        .filter(|(_, pos)| sudoku.at(*pos) == 0)
+        .map(|(digit, pos)| (pos, digit))
+        .sorted_by_key(|((x, y), digit)| (*y, *x, *digit))
+        .dedup()
+        .collect();
+      assert_eq!(expected, viable_candidates(&sudoku, &lock));
+    }
+    let partial = Sudoku::load("1     3        4", 2);
+    assert!(viable_candidates(&partial, &lock).len() < partial.candidate_stats().0);
+  }
+
+  #[test]
+  fn solvable_boards() {
+    let minimal 
       = "".to_string()
       + "   " + "   " + " 1 "
       + "   " + "  2" + "  3"
@@ -248,18 +1916,9 @@ mod test {
       + " 5 " + "   " + "2  "
       + "   " + " 8 " + " 4 "
       + " 3 " + "91 " + "   ";
-  
-    let sudoku = Sudoku::load(data.as_str(), 3);
-    let solutions = time!(collect_all(solution_iter(sudoku, &AbortLock::prepare())));
-    assert!(solutions.len() == 1);
-    assert!(solutions[0].is_solved());
-  }
-
-  #[test]
-  fn another_hard_sudoku_solution() {
-    let data 
+    let corrupted 
       = "".to_string()
-      + "   " + "   " + "   "
+      + "9  " + "   " + "   "
       + " 9 " + " 1 " + " 3 "
       + "  6" + " 2 " + "7  "
 
@@ -270,14 +1929,41 @@ mod test {
       + "  2" + "5 6" + "4  "
       + " 8 " + "   " + " 1 "
       + "   " + "   " + "   ";
-    
-    let sudoku = Sudoku::load(data.as_str(), 3);
-    let solution = time!(solution(sudoku, &AbortLock::prepare()));
-    assert!(solution.is_some_and(|x| x.is_solved()));
+
+    let lock = AbortLock::prepare();
+    assert!(is_solvable(&Sudoku::load(minimal.as_str(), 3), &lock));
+    assert!(!is_solvable(&Sudoku::load(corrupted.as_str(), 3), &lock));
+    assert!(!is_solvable(&Sudoku::empty(2), &AbortLock::aborted()));
   }
 
   #[test]
-  fn corrupted_sudoku_solution() {
+  fn moves_keeping_solvability() {
+    let medium 
+      = "".to_string()
+      + "  8" + "  3" + "461"
+      + "2 6" + " 84" + "   "
+      + "3  " + "  7" + " 9 "
+
+      + " 3 " + "75 " + "68 "
+      + " 87" + " 1 " + "   "
+      + " 5 " + " 4 " + "13 "
+
+      + "  9" + "27 " + "31 "
+      + "   " + "   " + "   "
+      + "763" + "4 1" + "8 2";
+
+    let lock = AbortLock::prepare();
+    let sudoku = Sudoku::load(medium.as_str(), 3);
+    let solved = solution(sudoku.clone(), &lock).unwrap();
+    let correct = solved.at((0, 0));
+    assert!(still_solvable_after(&sudoku, (0, 0), correct, &lock));
+    let wrong = sudoku.available((0, 0)).iter().find(|digit| *digit != correct).unwrap();
+    assert!(!still_solvable_after(&sudoku, (0, 0), wrong, &lock));
+    assert!(!still_solvable_after(&sudoku, (0, 0), 8, &lock));
+  }
+
+  #[test]
+  fn corrupted_sudoku_diagnostic() {
     let data 
       = "".to_string()
       + "9  " + "   " + "   "
@@ -291,10 +1977,16 @@ mod test {
       + "  2" + "5 6" + "4  "
       + " 8 " + "   " + " 1 "
       + "   " + "   " + "   ";
-    
+
+    let lock = AbortLock::prepare();
     let sudoku = Sudoku::load(data.as_str(), 3);
-    let solution = time!(solution(sudoku, &AbortLock::prepare()));
-    assert!(solution.is_none());
+    let blamed = solution_diagnostic(sudoku, &lock).unwrap_err();
+    assert!(blamed.contains(&(0, 0)));
+    assert!(blamed.contains(&(1, 1)));
+
+    let blocked = Sudoku::load("  123   4       ", 2);
+    assert_eq!(Err(vec![(2, 0), (3, 0), (0, 1), (0, 2)]), solution_diagnostic(blocked, &lock));
+    assert!(solution_diagnostic(Sudoku::empty(2), &lock).is_ok_and(|solved| solved.is_solved()));
   }
 
   #[test]
@@ -317,6 +2009,51 @@ mod test {
     assert_eq!(Some((3, (7, 8), 1)), hint(sudoku, 3, &AbortLock::prepare()));
   }
 
+  #[test]
+  fn medium_sudoku_most_constrained() {
+    let data 
+      = "".to_string()
+      + "  8" + "  3" + "461"
+      + "2 6" + " 84" + "   "
+      + "3  " + "  7" + " 9 "
+
+      + " 3 " + "75 " + "68 "
+      + " 87" + " 1 " + "   "
+      + " 5 " + " 4 " + "13 "
+
+      + "  9" + "27 " + "31 "
+      + "   " + "   " + "   "
+      + "763" + "4 1" + "8 2";
+
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    assert_eq!(Some(((2, 5), Flags::empty().set(2))), most_constrained_empty(&sudoku));
+  }
+
+  #[test]
+  fn contradiction_depth_test() {
+    let lock = AbortLock::prepare();
+    let shallow 
+      = " 3 3".to_string()
+      + "    "
+      + "    "
+      + "    ";
+    let deep 
+      = " 3  ".to_string()
+      + "   4"
+      + " 12 "
+      + "    ";
+
+    assert_eq!(Some(1), contradiction_depth(&Sudoku::load(shallow.as_str(), 2), 3, &lock));
+    assert_eq!(Some(2), contradiction_depth(&Sudoku::load(deep.as_str(), 2), 3, &lock));
+    assert_eq!(None, contradiction_depth(&Sudoku::load(deep.as_str(), 2), 1, &lock));
+    assert_eq!(None, contradiction_depth(&Sudoku::empty(2), 3, &lock));
+
+    let deep = Sudoku::load(deep.as_str(), 2);
+    for level in 1 ..= 3 {
+      assert_eq!(contradiction(&deep, level, &lock), contradiction_depth(&deep, 3, &lock).is_some_and(|d| d <= level));
+    }
+  }
+
   #[test]
   fn hardest_for_logic_sudoku_solution() {
     let data 
@@ -359,6 +2096,32 @@ mod test {
     assert!(solution.is_some_and(|x| x.is_solved()));
   }
 
+  #[test]
+  fn hard_sudoku_branch_strategies() {
+    let data 
+      = "".to_string()
+      + "3  " + " 2 " + "   "
+      + "  7" + "6  " + "  3"
+      + "6  " + "9  " + "  4"
+
+      + "2  " + "1  " + "6  "
+      + "9  " + "8 3" + " 5 "
+      + "  5" + "4  " + "7  "
+
+      + " 8 " + "  9" + " 1 "
+      + "   " + " 7 " + "2  "
+      + " 4 " + "  6" + "3  ";
+
+    let lock = AbortLock::prepare();
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    for strategy in [BranchStrategy::MinCandidates, BranchStrategy::MinCandidatesThenLocality] {
+      let solved = time!(solution_with_strategy(sudoku.clone(), strategy, &lock)).unwrap();
+      assert!(solved.is_solved());
+      assert!(sudoku.iter().all(|(digit, pos)| digit == 0 || solved.at(pos) == digit));
+      assert_eq!(solution(sudoku.clone(), &lock), Some(solved));
+    }
+  }
+
   #[test]
   fn medium_sudoku_solution() {
     let data 
@@ -401,6 +2164,30 @@ mod test {
     assert!(solution.is_some_and(|x| x.is_solved()));
   }
 
+  #[test]
+  fn hard_for_brute_force_lookahead_bail_out() {
+    let data 
+      = "".to_string()
+      + "   " + "   " + "   "
+      + "   " + "  3" + " 85"
+      + "  1" + " 2 " + "   "
+
+      + "   " + "5 7" + "   "
+      + "  4" + "   " + "1  "
+      + " 9 " + "   " + "   "
+
+      + "5  " + "   " + " 73"
+      + "  2" + " 1 " + "   "
+      + "   " + " 4 " + "  9";
+
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    let lock = AbortLock::prepare();
+    let with_bail_out = time!(Solver::new(&lock).solve(&sudoku));
+    let without_bail_out = time!(Solver::new(&lock).with_lookahead_limit(u8::MAX).solve(&sudoku));
+    assert!(with_bail_out.as_ref().is_some_and(|x| x.is_solved()));
+    assert_eq!(with_bail_out, without_bail_out);
+  }
+
   #[test]
   fn hard_for_brute_force_sudoku_all_solutions() {
     let data 
@@ -423,6 +2210,139 @@ mod test {
     assert!(solutions[0].is_solved());
   }
 
+  #[test]
+  fn hard_for_brute_force_counting_progress() {
+    let data 
+      = "".to_string()
+      + "   " + "   " + "   "
+      + "   " + "  3" + " 85"
+      + "  1" + " 2 " + "   "
+
+      + "   " + "5 7" + "   "
+      + "  4" + "   " + "1  "
+      + " 9 " + "   " + "   "
+
+      + "5  " + "   " + " 73"
+      + "  2" + " 1 " + "   "
+      + "   " + " 4 " + "  9";
+
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    let mut reports = vec![];
+    let count = time!(count_solutions_with_progress(sudoku.clone(), 2, &AbortLock::prepare(), 10, 
+      |nodes| reports.push(nodes)));
+    assert_eq!(1, count);
+    assert!(!reports.is_empty());
+    assert!(reports.iter().all(|nodes| nodes % 10 == 0));
+
+    assert_eq!(0, count_solutions(sudoku.clone(), 0, &AbortLock::prepare()));
+    assert_eq!(0, count_solutions_with_progress(sudoku, 0, &AbortLock::prepare(), 10, |_| {}));
+  }
+
+  #[test]
+  fn search_metrics_of_easy_and_hard() {
+    let trivial 
+      = "   3".to_string()
+      + "3  2"
+      + "2  1"
+      + "1   ";
+    let hard 
+      = "".to_string()
+      + "8  " + "   " + "   "
+      + "  3" + "6  " + "   "
+      + " 7 " + "  9" + "2  "
+
+      + " 5 " + "  7" + "   "
+      + "   " + " 45" + "7  "
+      + "   " + "1  " + " 3 "
+
+      + "  1" + "   " + " 68"
+      + "  8" + "5  " + " 1 "
+      + " 9 " + "   " + "4  ";
+    let lock = AbortLock::prepare();
+
+    let easy = search_metrics(Sudoku::load(trivial.as_str(), 2), &lock).unwrap();
+    assert_eq!(SearchMetrics { nodes: 1, max_depth: 0, guesses: 0 }, easy);
+
+    let hard = time!(search_metrics(Sudoku::load(hard.as_str(), 3), &lock).unwrap());
+    assert!(hard.guesses > 0);
+    assert!(hard.max_depth > 1);
+    assert!(hard.nodes > hard.guesses);
+    assert_eq!(None, search_metrics(Sudoku::load("  123   4       ", 2), &lock));
+  }
+
+  #[test]
+  fn backtracks_as_effort() {
+    let trivial 
+      = "   3".to_string()
+      + "3  2"
+      + "2  1"
+      + "1   ";
+    let hard 
+      = "".to_string()
+      + "   " + "   " + "   "
+      + "   " + "  3" + " 85"
+      + "  1" + " 2 " + "   "
+
+      + "   " + "5 7" + "   "
+      + "  4" + "   " + "1  "
+      + " 9 " + "   " + "   "
+
+      + "5  " + "   " + " 73"
+      + "  2" + " 1 " + "   "
+      + "   " + " 4 " + "  9";
+    let non_unique 
+      = "".to_string()
+      + "   " + "   " + "   "
+      + " 9 " + " 1 " + " 3 "
+      + "  6" + " 2 " + "7  "
+
+      + "   " + "3 4" + "   "
+      + "21 " + "   " + " 98"
+      + "   " + "   " + "   "
+
+      + "  2" + "5 6" + "   "
+      + " 8 " + "   " + "   "
+      + "   " + "   " + "   ";
+    let lock = AbortLock::prepare();
+
+    let (solved, backtracks) = solution_with_effort(Sudoku::load(trivial.as_str(), 2), &lock).unwrap();
+    assert!(solved.is_solved());
+    assert!(backtracks <= 2);
+
+    let (solved, backtracks) = time!(solution_with_effort(Sudoku::load(hard.as_str(), 3), &lock).unwrap());
+    assert!(solved.is_solved());
+    assert!(backtracks > 1000);
+
+    let non_unique = Sudoku::load(non_unique.as_str(), 3);
+    let (solved, _) = solution_with_effort(non_unique.clone(), &lock).unwrap();
+    assert_eq!(solution(non_unique, &lock), Some(solved));
+    assert_eq!(None, solution_with_effort(Sudoku::load("1111111111111111", 2), &lock));
+  }
+
+  #[test]
+  fn hard_for_brute_force_cached_solution() {
+    let data 
+      = "".to_string()
+      + "   " + "   " + "   "
+      + "   " + "  3" + " 85"
+      + "  1" + " 2 " + "   "
+
+      + "   " + "5 7" + "   "
+      + "  4" + "   " + "1  "
+      + " 9 " + "   " + "   "
+
+      + "5  " + "   " + " 73"
+      + "  2" + " 1 " + "   "
+      + "   " + " 4 " + "  9";
+
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    let plain = time!(solution(sudoku.clone(), &AbortLock::prepare()));
+    let cached = time!(solution_cached(sudoku, &AbortLock::prepare()));
+    assert!(cached.as_ref().is_some_and(|x| x.is_solved()));
+    assert_eq!(plain, cached);
+    assert_eq!(None, solution_cached(Sudoku::load("1111111111111111", 2), &AbortLock::prepare()));
+  }
+
   #[ignore = "It takes to long to finish"] 
   #[test]
   fn non_unique_solution_test() {
@@ -445,4 +2365,65 @@ mod test {
     assert!(solutions.len() > 1);
     assert!(solutions.iter().all(|x| x.is_solved()));
   }
+
+  #[test]
+  fn ambiguous_cells_test() {
+    let data 
+      = "".to_string()
+      + "   " + "   " + "   "
+      + " 9 " + " 1 " + " 3 "
+      + "  6" + " 2 " + "7  "
+
+      + "   " + "3 4" + "   "
+      + "21 " + "   " + " 98"
+      + "   " + "   " + "   "
+
+      + "  2" + "5 6" + "   "
+      + " 8 " + "   " + "   "
+      + "   " + "   " + "   ";
+    let lock = AbortLock::prepare();
+    let sudoku = Sudoku::load(data.as_str(), 3);
+
+    let cells = ambiguous_cells(&sudoku, &lock);
+    assert!(cells.len() >= 2);
+    assert!(cells.iter().all(|pos| sudoku.at(*pos) == 0));
+    let solutions = collect_all(Box::new(solution_iter(sudoku.clone(), &lock).take(2)));
+    assert!(sudoku.iter()
+      .all(|(_, pos)| cells.contains(&pos) == (solutions[0].at(pos) != solutions[1].at(pos))));
+
+    let unique 
+      = "   3".to_string()
+      + "3  2"
+      + "2  1"
+      + "1   ";
+    assert!(ambiguous_cells(&Sudoku::load(unique.as_str(), 2), &lock).is_empty());
+  }
+
+  #[test]
+  fn non_unique_solution_reproducible() {
+    let data 
+      = "".to_string()
+      + "   " + "   " + "   "
+      + " 9 " + " 1 " + " 3 "
+      + "  6" + " 2 " + "7  "
+
+      + "   " + "3 4" + "   "
+      + "21 " + "   " + " 98"
+      + "   " + "   " + "   "
+
+      + "  2" + "5 6" + "   "
+      + " 8 " + "   " + "   "
+      + "   " + "   " + "   ";
+    let lock = AbortLock::prepare();
+    let sudoku = Sudoku::load(data.as_str(), 3);
+
+    let first = solution(sudoku.clone(), &lock).map(|s| s.save());
+    assert!(first.is_some());
+    assert!((0 .. 3).all(|_| solution(sudoku.clone(), &lock).map(|s| s.save()) == first));
+
+    let small = Sudoku::empty(2).set((1, 1), 3);
+    let first = solution(small.clone(), &lock).map(|s| s.save());
+    assert!(first.is_some());
+    assert!((0 .. 100).all(|_| solution(small.clone(), &lock).map(|s| s.save()) == first));
+  }
 }
\ No newline at end of file
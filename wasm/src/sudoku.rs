@@ -7,26 +7,184 @@ use crate::flags::*;
 
 pub type Position = (usize, usize);
 
+/// Largest supported box size, digits of bigger boards wouldn't fit into `Flags`
+/// nor into the one character per field format
+pub const MAX_BOX_SIZE: u8 = 5;
+
+/// Largest box size `canonical_form` handles, the number of layouts it compares grows
+/// as `(box_size!)^(2 * box_size + 2)`, which is already about 1.3e14 for 16x16
+pub const CANONICAL_MAX_BOX_SIZE: u8 = 3;
+
+/// Reasons why a sudoku could not be loaded
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum LoadError {
+  /// Input does not describe exactly `board_size²` cells
+  WrongLength { expected: usize, found: usize },
+  /// Given clues already break the rules, every clashing pair of positions is listed
+  Conflict(Vec<(Position, Position)>),
+  /// Grid does not have `board_size` rows
+  WrongRowCount { expected: usize, found: usize },
+  /// Row of the grid does not have `board_size` fields
+  WrongRowLength { row: usize, expected: usize, found: usize },
+  /// Digit does not fit on the board
+  DigitOutOfRange { pos: Position, digit: u8 },
+  /// Character at given index of the input is neither a digit nor an empty field
+  InvalidCharacter { index: usize, character: char },
+  /// Jigsaw region does not have `board_size` fields
+  WrongRegionSize { region: usize, expected: usize, found: usize },
+  /// Box size is 0 or bigger than `MAX_BOX_SIZE`
+  UnsupportedBoxSize(u8),
+  /// Cage at given index of the cage list covers a field outside of the board
+  CageCellOutside { cage: usize, pos: Position },
+}
+
+/// Reasons why a digit can not be written into a field
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PlacementError {
+  /// Digit is not between 1 and `board_size`
+  DigitOutOfRange(u8),
+  /// Same digit is already at given position in the row
+  InRow(Position),
+  /// Same digit is already at given position in the column
+  InColumn(Position),
+  /// Same digit is already at given position in the box
+  InBox(Position),
+  /// Field holds one of the original clues of the puzzle
+  Given,
+}
+
+impl fmt::Display for LoadError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      LoadError::WrongLength { expected, found } =>
+        write!(f, "expected {} cells, found {}", expected, found),
+      LoadError::Conflict(pairs) => {
+        let described = pairs.iter()
+          .map(|(p1, p2)| format!("{:?} and {:?}", p1, p2))
+          .join(", ");
+        write!(f, "conflicting clues at {}", described)
+      }
+      LoadError::WrongRowCount { expected, found } =>
+        write!(f, "expected {} rows, found {}", expected, found),
+      LoadError::WrongRowLength { row, expected, found } =>
+        write!(f, "expected {} fields in row {}, found {}", expected, row, found),
+      LoadError::DigitOutOfRange { pos, digit } =>
+        write!(f, "digit {} at {:?} does not fit on the board", digit, pos),
+      LoadError::InvalidCharacter { index, character } =>
+        write!(f, "unexpected character {:?} at index {}", character, index),
+      LoadError::WrongRegionSize { region, expected, found } =>
+        write!(f, "expected {} fields in region {}, found {}", expected, region, found),
+      LoadError::UnsupportedBoxSize(box_size) =>
+        write!(f, "box size {} is not between 1 and {}", box_size, MAX_BOX_SIZE),
+      LoadError::CageCellOutside { cage, pos } =>
+        write!(f, "cell {:?} of cage {} is outside of the board", pos, cage),
+    }
+  }
+}
+
+/// Killer sudoku cage, its digits have to be distinct and add up to `sum`
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cage {
+  pub cells: Vec<Position>,
+  pub sum: u32,
+}
+
 #[derive(Clone, PartialEq, PartialOrd, Ord, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "SudokuData", into = "SudokuData"))]
 pub struct Sudoku {
   size: u8,
   board: Vec<Vec<u8>>,
+  cages: Vec<Cage>,
+  /// Jigsaw region of every field in reading order, classic boxes when missing
+  regions: Option<Vec<usize>>,
+  /// Fields a chess knight's move apart may not hold the same digit
+  anti_knight: bool,
+  /// Extra windoku regions between the boxes have to hold every digit too
+  windoku: bool,
+}
+
+/// Serialized form of sudoku with cells listed row by row, 0 marks an empty field
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SudokuData {
+  box_size: u8,
+  cells: Vec<u8>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  cages: Vec<Cage>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  regions: Option<Vec<usize>>,
+  #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+  anti_knight: bool,
+  #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+  windoku: bool,
+}
+
+#[cfg(feature = "serde")]
+impl From<Sudoku> for SudokuData {
+  fn from(sudoku: Sudoku) -> Self {
+    SudokuData { 
+      box_size: sudoku.size,
+      cells: sudoku.board.into_iter().flatten().collect(),
+      cages: sudoku.cages,
+      regions: sudoku.regions,
+      anti_knight: sudoku.anti_knight,
+      windoku: sudoku.windoku
+    }
+  }
+}
+
+#[cfg(feature = "serde")]
+impl std::convert::TryFrom<SudokuData> for Sudoku {
+  type Error = LoadError;
+
+  fn try_from(data: SudokuData) -> Result<Self, Self::Error> {
+    Sudoku::check_box_size(data.box_size)?;
+    let board_size = data.box_size as usize * data.box_size as usize;
+    if data.cells.len() != board_size * board_size {
+      return Err(LoadError::WrongLength { expected: board_size * board_size, found: data.cells.len() });
+    }
+    if let Some((index, digit)) = data.cells.iter().enumerate().find(|(_, digit)| **digit as usize > board_size) {
+      return Err(LoadError::DigitOutOfRange { pos: (index % board_size, index / board_size), digit: *digit });
+    }
+    let outside = data.cages.iter().enumerate()
+      .find_map(|(cage, c)| c.cells.iter().find(|(x, y)| *x >= board_size || *y >= board_size).map(|pos| (cage, *pos)));
+    if let Some((cage, pos)) = outside {
+      return Err(LoadError::CageCellOutside { cage, pos });
+    }
+    let board = data.cells.chunks(board_size).map(|row| row.to_vec()).collect();
+    let sudoku = Sudoku { 
+      size: data.box_size, board, cages: data.cages, regions: None, anti_knight: data.anti_knight, windoku: data.windoku
+    };
+    match data.regions {
+      Some(regions) => sudoku.with_regions(regions),
+      None => Ok(sudoku)
+    }
+  }
 }
 
 impl Sudoku {
+  /// Board of given box size with all fields empty, panics when the box size is not
+  /// supported
   pub fn empty(box_size: u8) -> Sudoku {
+    Sudoku::assert_box_size(box_size);
     let board_size = (box_size * box_size) as usize;
     Sudoku {
       size: box_size,
-      board: vec![vec![0; board_size]; board_size]
+      board: vec![vec![0; board_size]; board_size],
+      cages: vec![],
+      regions: None,
+      anti_knight: false,
+      windoku: false
     }
   }
 
   fn digit_to_char(d: u8) -> char {
     match d {
       0 => ' ',
-      1 ..= 9 => ('0' as u8 + d) as char,
-      10 ..= 25 => ('A' as u8 - 10 + d) as char,
+      1 ..= 9 => (b'0' + d) as char,
+      10 ..= 25 => (b'A' - 10 + d) as char,
       _ => '#'
     }
   }
@@ -34,16 +192,48 @@ impl Sudoku {
   fn char_to_digit(c: char) -> u8 {
     match c {
       ' ' => 0,
-      '1' ..= '9' => c as u8 - '0' as u8,
-      'A' ..= 'Z' => c as u8 - 'A' as u8 + 10,
+      '1' ..= '9' => c as u8 - b'0',
+      'A' ..= 'Z' => c as u8 - b'A' + 10,
       _ => 26
     }
   }
 
+  /// Digit written by the character in text boards: space, `.` and `0` mark an empty
+  /// field, `1` to `9` and `A` to `Z` the digits from 1 up. Whether the digit fits the
+  /// board is left to the caller.
+  pub(crate) fn cell_digit(character: char) -> Option<u8> {
+    match character {
+      ' ' | '.' | '0' => Some(0),
+      '1' ..= '9' | 'A' ..= 'Z' => Some(Sudoku::char_to_digit(character)),
+      _ => None
+    }
+  }
+
+  /// Characters separating fields in pasted boards, like line breaks and box borders
+  pub(crate) fn is_separator(character: char) -> bool {
+    matches!(character, '\n' | '\r' | '\t' | '|' | '-' | '+' | ',' | ';')
+  }
+
+  /// Checks that boards with given box size can be built
+  pub fn check_box_size(box_size: u8) -> Result<(), LoadError> {
+    match box_size {
+      1 ..= MAX_BOX_SIZE => Ok(()),
+      _ => Err(LoadError::UnsupportedBoxSize(box_size))
+    }
+  }
+
+  fn assert_box_size(box_size: u8) {
+    if let Err(error) = Sudoku::check_box_size(box_size) {
+      panic!("{}", error);
+    }
+  }
+
+  /// Panics when the box size is not supported
   pub fn load(data_str: &str, box_size: u8) -> Sudoku {
+    Sudoku::assert_box_size(box_size);
     let board_size = (box_size * box_size) as usize;
     let data: Vec<u8> = data_str.chars()
-      .map(|x| Sudoku::char_to_digit(x))
+      .map(Sudoku::char_to_digit)
       .collect();
 
     let board = (0 .. board_size)
@@ -52,12 +242,94 @@ impl Sudoku {
         acc
       });
 
-      Sudoku { size: box_size, board }
+      Sudoku { size: box_size, board, cages: vec![], regions: None, anti_knight: false, windoku: false }
+  }
+
+  /// Loads sudoku like `load`, but rejects input of wrong length or with clues
+  /// that already clash with each other
+  pub fn load_checked(data_str: &str, box_size: u8) -> Result<Sudoku, LoadError> {
+    Sudoku::check_box_size(box_size)?;
+    let board_size = box_size as usize * box_size as usize;
+    let found = data_str.chars().count();
+    if found != board_size * board_size {
+      return Err(LoadError::WrongLength { expected: board_size * board_size, found });
+    }
+
+    let sudoku = Sudoku::load(data_str, box_size);
+    let conflicts = sudoku.conflicts();
+    if conflicts.is_empty() {
+      Ok(sudoku)
+    } else {
+      Err(LoadError::Conflict(conflicts))
+    }
+  }
+
+  /// Loads sudoku rejecting every character which is neither a digit fitting the
+  /// board nor an empty field marked with space, `.` or `0`, and input of wrong length
+  pub fn load_strict(data_str: &str, box_size: u8) -> Result<Sudoku, LoadError> {
+    Sudoku::check_box_size(box_size)?;
+    let board_size = box_size as usize * box_size as usize;
+    let data = data_str.chars().enumerate()
+      .map(|(index, character)| match Sudoku::cell_digit(character) {
+        Some(digit) if digit as usize <= board_size => Ok(digit),
+        _ => Err(LoadError::InvalidCharacter { index, character }),
+      })
+      .collect::<Result<Vec<u8>, LoadError>>()?;
+    if data.len() != board_size * board_size {
+      return Err(LoadError::WrongLength { expected: board_size * board_size, found: data.len() });
+    }
+
+    let board = data.chunks(board_size).map(|row| row.to_vec()).collect();
+    Ok(Sudoku { size: box_size, board, cages: vec![], regions: None, anti_knight: false, windoku: false })
+  }
+
+  /// Loads a grid pasted as ASCII art like `load_strict`, line breaks, tabs and the
+  /// `|`, `-`, `+`, `,` and `;` characters drawing box borders are skipped. Spaces
+  /// still mark empty fields.
+  pub fn load_pretty(data_str: &str, box_size: u8) -> Result<Sudoku, LoadError> {
+    let cells: String = data_str.chars()
+      .filter(|c| !Sudoku::is_separator(*c))
+      .collect();
+    Sudoku::load_strict(&cells, box_size)
+  }
+
+  /// Builds sudoku from rows of digits, 0 marks an empty field
+  pub fn from_grid(grid: &[&[u8]], box_size: u8) -> Result<Sudoku, LoadError> {
+    Sudoku::check_box_size(box_size)?;
+    let board_size = box_size as usize * box_size as usize;
+    if grid.len() != board_size {
+      return Err(LoadError::WrongRowCount { expected: board_size, found: grid.len() });
+    }
+    if let Some((row, fields)) = grid.iter().enumerate().find(|(_, r)| r.len() != board_size) {
+      return Err(LoadError::WrongRowLength { row, expected: board_size, found: fields.len() });
+    }
+
+    let sudoku = Sudoku::empty(box_size);
+    grid.iter().enumerate()
+      .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, digit)| ((x, y), *digit)))
+      .try_fold(sudoku, |acc, (pos, digit)| {
+        if digit as usize > board_size {
+          Err(LoadError::DigitOutOfRange { pos, digit })
+        } else {
+          Ok(acc.set(pos, digit))
+        }
+      })
+  }
+
+  /// Puzzle showing only the listed fields of a solved board as clues, rules like cages
+  /// and regions are kept. Panics when `solution` is not solved.
+  pub fn from_solution_and_mask(solution: &Sudoku, keep: &[Position]) -> Sudoku {
+    assert!(solution.is_solved(), "clues have to be taken from a solved board");
+    let mut puzzle = solution.clone();
+    for (_, pos) in solution.iter().filter(|(_, pos)| !keep.contains(pos)) {
+      puzzle.put(pos, 0);
+    }
+    puzzle
   }
 
   pub fn save(&self) -> String {
     self.board.iter()
-      .flat_map(|v| v)
+      .flatten()
       .map(|x| Sudoku::digit_to_char(*x))
       .join("")
   }
@@ -90,6 +362,132 @@ impl Sudoku {
       }).join("")
   }
 
+  /// Grid framed with `+---+` lines around every band of boxes and `|` around every
+  /// group of fields, empty fields are shown as `.`. `load_pretty` reads it back.
+  pub fn pretty(&self) -> String {
+    let separator_line = format!("+{}\n", format!("{}+", "-".repeat(self.box_size())).repeat(self.box_size()));
+    let lines = self.board.iter().map(|row| {
+      let groups = row.chunks(self.box_size())
+        .map(|group| group.iter()
+          .map(|digit| match digit {
+            0 => '.',
+            _ => Sudoku::digit_to_char(*digit)
+          })
+          .collect::<String>())
+        .join("|");
+      format!("|{}|\n", groups)
+    });
+    lines.chunks(self.box_size()).into_iter()
+      .fold(separator_line.clone(), |acc, band| acc + &band.collect::<String>() + &separator_line)
+  }
+
+  /// Every order of lines reachable by permuting bands and lines within each band
+  fn line_orders(&self) -> Vec<Vec<usize>> {
+    let box_size = self.box_size();
+    (0 .. box_size).permutations(box_size)
+      .flat_map(|bands| bands.into_iter()
+        .map(|band| (band * box_size .. (band + 1) * box_size).permutations(box_size))
+        .multi_cartesian_product()
+        .map(|lines| lines.concat()))
+      .collect()
+  }
+
+  /// Representative shared by all boards equal up to relabeling digits, permuting
+  /// bands, stacks and lines within them, and transposing. Digits are relabeled in
+  /// order of first appearance, which gives the smallest board for a fixed layout,
+  /// and the smallest board over all `2 * (box_size!)^(2 * box_size + 2)` layouts is
+  /// kept (about 3.4 million for 9x9). Each layout is dropped at its first field
+  /// larger than the best one, so most of them cost only a few comparisons.
+  /// Killer cages and the anti-knight and windoku rules are ignored. Gives `None` for
+  /// boxes bigger than `CANONICAL_MAX_BOX_SIZE` and for jigsaw boards, whose regions
+  /// don't survive moving lines around.
+  pub fn canonical_form(&self) -> Option<Sudoku> {
+    if self.size > CANONICAL_MAX_BOX_SIZE || self.regions.is_some() {
+      return None;
+    }
+    let board_size = self.board_size();
+    let orders = self.line_orders();
+    let mut best: Option<Vec<u8>> = None;
+
+    for transpose in [false, true] {
+      for rows in orders.iter() {
+        for columns in orders.iter() {
+          let mut labels = vec![0; board_size + 1];
+          let mut next_label = 1;
+          let mut candidate = Vec::with_capacity(board_size * board_size);
+          let mut smaller = best.is_none();
+
+          for (x, y) in (0 .. board_size).flat_map(|y| (0 .. board_size).map(move |x| (x, y))) {
+            let source = if transpose { (rows[y], columns[x]) } else { (columns[x], rows[y]) };
+            let digit = self.at(source) as usize;
+            if digit != 0 && labels[digit] == 0 {
+              labels[digit] = next_label;
+              next_label += 1;
+            }
+            let label = labels[digit];
+
+            if let (false, Some(best)) = (smaller, &best) {
+              let best_label = best[candidate.len()];
+              if label > best_label {
+                break;
+              }
+              smaller = label < best_label;
+            }
+            candidate.push(label);
+          }
+
+          if candidate.len() == board_size * board_size {
+            best = Some(candidate);
+          }
+        }
+      }
+    }
+
+    let board = best.unwrap_or_default()
+      .chunks(board_size)
+      .map(|row| row.to_vec())
+      .collect();
+    Some(Sudoku { size: self.size, board, cages: vec![], regions: None, anti_knight: false, windoku: false })
+  }
+
+  /// Board with every field and cage cell moved to `target(pos)`
+  fn moved(&self, target: impl Fn(Position) -> Position) -> Sudoku {
+    let mut board = vec![vec![0; self.board_size()]; self.board_size()];
+    for (digit, pos) in self.iter() {
+      let (x, y) = target(pos);
+      board[y][x] = digit;
+    }
+    let cages = self.cages.iter()
+      .map(|cage| Cage { cells: cage.cells.iter().map(|pos| target(*pos)).collect(), sum: cage.sum })
+      .collect();
+    let regions = self.regions.as_ref().map(|regions| {
+      let mut moved = vec![0; regions.len()];
+      for (_, (x, y)) in self.iter() {
+        let (new_x, new_y) = target((x, y));
+        moved[new_y * self.board_size() + new_x] = regions[y * self.board_size() + x];
+      }
+      moved
+    });
+    Sudoku { size: self.size, board, cages, regions, anti_knight: self.anti_knight, windoku: self.windoku }
+  }
+
+  /// Mirrors the board along the diagonal from top left to bottom right
+  pub fn transpose(&self) -> Sudoku {
+    self.moved(|(x, y)| (y, x))
+  }
+
+  /// Turns the board clockwise by a quarter
+  pub fn rotate90(&self) -> Sudoku {
+    let last = self.board_size() - 1;
+    self.moved(|(x, y)| (last - y, x))
+  }
+
+  /// Swaps left and right side of the board
+  pub fn mirror_horizontal(&self) -> Sudoku {
+    let last = self.board_size() - 1;
+    self.moved(|(x, y)| (last - x, y))
+  }
+
   pub fn board_size(&self) -> usize {
     (self.size * self.size) as usize
   }
@@ -102,21 +500,339 @@ impl Sudoku {
     self.board[y][x]
   }
 
+  /// Digit in the field (0 for empty) with bounds checked against the board size
+  pub fn get(&self, (x, y): Position) -> u8 {
+    let n = self.board_size();
+    if x >= n || y >= n {
+      panic!("position ({}, {}) is outside of {}x{} board", x, y, n, n);
+    }
+    self.board[y][x]
+  }
+
+  /// Digits of every row from top to bottom, 0 for empty fields
+  pub fn rows(&self) -> Vec<Vec<u8>> {
+    self.board.clone()
+  }
+
+  /// Digits of every column from left to right, each read from top to bottom
+  pub fn columns(&self) -> Vec<Vec<u8>> {
+    (0 .. self.board_size())
+      .map(|x| self.column_positions(x).into_iter().map(|pos| self.at(pos)).collect())
+      .collect()
+  }
+
+  /// Digits of every box in reading order of the boxes, each read in reading order.
+  /// Jigsaw regions are listed by their numbers.
+  pub fn boxes(&self) -> Vec<Vec<u8>> {
+    (0 .. self.board_size())
+      .map(|index| self.region_positions(index).into_iter().map(|pos| self.at(pos)).collect())
+      .collect()
+  }
+
+  /// Writes value into the field, 0 makes the field empty again
   pub fn set(mut self, (x, y): Position, new_val: u8) -> Sudoku {
     self.board[y][x] = new_val;
     self
   }
 
+  /// Same as `set`, but changes the board in place
+  pub fn put(&mut self, (x, y): Position, new_val: u8) {
+    self.board[y][x] = new_val;
+  }
+
+  /// Overwrites this board with `other`, reusing already allocated rows
+  pub fn copy_from(&mut self, other: &Sudoku) {
+    self.size = other.size;
+    self.board.clone_from(&other.board);
+    self.cages.clone_from(&other.cages);
+    self.regions.clone_from(&other.regions);
+    self.anti_knight = other.anti_knight;
+    self.windoku = other.windoku;
+  }
+
+  /// Copy of the board with given field erased
+  pub fn clear(&self, pos: Position) -> Sudoku {
+    self.clone().set(pos, 0)
+  }
+
+  /// Writes digit into the field only when no rule is broken by it
+  pub fn try_set(&self, pos: Position, digit: u8) -> Result<Sudoku, PlacementError> {
+    if digit == 0 || digit as usize > self.board_size() {
+      return Err(PlacementError::DigitOutOfRange(digit));
+    }
+
+    let clash = |unit: Vec<Position>| unit.into_iter()
+      .find(|other| *other != pos && self.at(*other) == digit);
+    if let Some(other) = clash(self.row_positions(pos.1)) {
+      return Err(PlacementError::InRow(other));
+    }
+    if let Some(other) = clash(self.column_positions(pos.0)) {
+      return Err(PlacementError::InColumn(other));
+    }
+    if let Some(other) = clash(self.box_positions(pos)) {
+      return Err(PlacementError::InBox(other));
+    }
+    Ok(self.clone().set(pos, digit))
+  }
+
+  /// Number of empty fields
+  pub fn remaining_cells(&self) -> usize {
+    self.board.iter().flatten().filter(|d| **d == 0).count()
+  }
+
+  /// Part of the board already filled, from 0.0 for an empty board to 1.0 for a full one
+  pub fn completion_fraction(&self) -> f32 {
+    let cells = self.board_size() * self.board_size();
+    (cells - self.remaining_cells()) as f32 / cells as f32
+  }
+
+  /// Fields of two boards of the same size holding different values, in reading order.
+  /// Panics for boards of different sizes, like `diff`.
+  pub fn differing_cells(&self, other: &Sudoku) -> Vec<Position> {
+    self.diff(other).into_iter().map(|(pos, _, _)| pos).collect()
+  }
+
+  /// Every field of two boards of the same size holding different values, along with
+  /// its value on this board and on `other`, in reading order. Panics for boards of
+  /// different sizes.
+  pub fn diff(&self, other: &Sudoku) -> Vec<(Position, u8, u8)> {
+    assert_eq!(self.size, other.size, "only boards of the same size can be compared");
+    (0 .. self.board_size())
+      .flat_map(|y| (0 .. self.board_size()).map(move |x| (x, y)))
+      .filter(|pos| self.at(*pos) != other.at(*pos))
+      .map(|pos| (pos, self.at(pos), other.at(pos)))
+      .collect()
+  }
+
+  /// Checks whether both boards hold the same digits in every field, cages and
+  /// regions are not compared
+  pub fn same_solution(&self, other: &Sudoku) -> bool {
+    self.size == other.size && self.board == other.board
+  }
+
+  /// Total number of candidates over empty fields, the most candidates any of them has
+  /// and their average per empty field. All zeros for a full board.
+  pub fn candidate_stats(&self) -> (usize, usize, f32) {
+    let counts: Vec<usize> = self.iter()
+      .filter(|(d, _)| *d == 0)
+      .map(|(_, pos)| self.available(pos).size() as usize)
+      .collect();
+    let total = counts.iter().sum();
+    let max = counts.iter().copied().max().unwrap_or(0);
+    let average = if counts.is_empty() { 0.0 } else { total as f32 / counts.len() as f32 };
+    (total, max, average)
+  }
+
+  /// Digit which is hardest to place: among digits still missing somewhere, the one
+  /// which is a candidate of the fewest empty fields, ties go to the smaller digit.
+  /// Returned along with the number of fields still needing it, `None` once every
+  /// digit is placed `board_size` times.
+  pub fn scarcest_digit(&self) -> Option<(u8, usize)> {
+    let board_size = self.board_size();
+    let mut placed = vec![0; board_size + 1];
+    let mut spots = vec![0; board_size + 1];
+    for (digit, pos) in self.iter() {
+      match digit {
+        0 => self.available(pos).iter().for_each(|candidate| spots[candidate as usize] += 1),
+        _ => placed[digit as usize] += 1
+      }
+    }
+    (1 ..= board_size)
+      .filter(|digit| placed[*digit] < board_size)
+      .min_by_key(|digit| (spots[*digit], *digit))
+      .map(|digit| (digit as u8, board_size - placed[digit]))
+  }
+
+  /// Replaces boxes with jigsaw regions given as region number of every field in
+  /// reading order. Each of the `board_size` regions has to hold `board_size` fields.
+  pub fn with_regions(mut self, regions: Vec<usize>) -> Result<Sudoku, LoadError> {
+    let board_size = self.board_size();
+    if regions.len() != board_size * board_size {
+      return Err(LoadError::WrongLength { expected: board_size * board_size, found: regions.len() });
+    }
+    for region in 0 .. board_size {
+      let found = regions.iter().filter(|r| **r == region).count();
+      if found != board_size {
+        return Err(LoadError::WrongRegionSize { region, expected: board_size, found });
+      }
+    }
+    self.regions = Some(regions);
+    Ok(self)
+  }
+
+  /// Jigsaw region of every field in reading order, `None` for classic boxes
+  pub fn regions(&self) -> Option<&[usize]> {
+    self.regions.as_deref()
+  }
+
+  /// Adds killer cages to the board
+  pub fn with_cages(mut self, cages: Vec<Cage>) -> Sudoku {
+    self.cages.extend(cages);
+    self
+  }
+
+  pub fn cages(&self) -> &[Cage] {
+    &self.cages
+  }
+
+  /// Adds the anti-knight rule, fields a chess knight's move apart may not hold the
+  /// same digit
+  pub fn with_anti_knight(mut self) -> Sudoku {
+    self.anti_knight = true;
+    self
+  }
+
+  pub fn is_anti_knight(&self) -> bool {
+    self.anti_knight
+  }
+
+  /// Adds the windoku rule, `box_size - 1` extra boxes placed one field away from the
+  /// border with one field between them have to hold every digit too. For 9x9 these
+  /// are the four shaded 3x3 regions.
+  pub fn with_windoku(mut self) -> Sudoku {
+    self.windoku = true;
+    self
+  }
+
+  pub fn is_windoku(&self) -> bool {
+    self.windoku
+  }
+
+  /// Fields of every extra windoku region, regions and their fields in reading order.
+  /// Empty for boards without the windoku rule.
+  pub fn windoku_regions(&self) -> Vec<Vec<Position>> {
+    if !self.windoku {
+      return vec![];
+    }
+    let size = self.box_size();
+    let starts: Vec<usize> = (0 .. size - 1).map(|i| 1 + i * (size + 1)).collect();
+    starts.iter()
+      .flat_map(|top| starts.iter().map(move |left| self.windoku_region_at(*left, *top)))
+      .collect()
+  }
+
+  fn windoku_region_at(&self, left: usize, top: usize) -> Vec<Position> {
+    (0 .. self.board_size())
+      .map(|i| (left + i % self.box_size(), top + i / self.box_size()))
+      .collect()
+  }
+
+  /// First row or column of the windoku regions covering given row or column
+  fn windoku_start(&self, line: usize) -> Option<usize> {
+    let size = self.box_size();
+    let (index, offset) = (line.checked_sub(1)? / (size + 1), line.checked_sub(1)? % (size + 1));
+    (index + 1 < size && offset < size).then(|| 1 + index * (size + 1))
+  }
+
+  /// Windoku region holding given field, if there is any
+  fn windoku_region_of(&self, (x, y): Position) -> Option<Vec<Position>> {
+    if !self.windoku {
+      return None;
+    }
+    Some(self.windoku_region_at(self.windoku_start(x)?, self.windoku_start(y)?))
+  }
+
+  /// Fields a chess knight's move away from given position, in reading order. They
+  /// only constrain the field on anti-knight boards.
+  pub fn knight_peers(&self, (x, y): Position) -> Vec<Position> {
+    let n = self.board_size() as isize;
+    [(-1, -2), (1, -2), (-2, -1), (2, -1), (-2, 1), (2, 1), (-1, 2), (1, 2)].iter()
+      .map(|(dx, dy)| (x as isize + dx, y as isize + dy))
+      .filter(|(x, y)| (0 .. n).contains(x) && (0 .. n).contains(y))
+      .map(|(x, y)| (x as usize, y as usize))
+      .collect()
+  }
+
+  /// Checks whether already filled digits of the cage repeat, exceed its sum
+  /// or fill it completely with a different sum
+  pub fn is_cage_broken(&self, cage: &Cage) -> bool {
+    let filled: Vec<u8> = cage.cells.iter()
+      .map(|pos| self.at(*pos))
+      .filter(|d| *d != 0)
+      .collect();
+    let total: u32 = filled.iter().map(|d| *d as u32).sum();
+
+    !filled.iter().all_unique()
+      || total > cage.sum
+      || (filled.len() == cage.cells.len() && total != cage.sum)
+  }
+
+  /// Digits the cage rules out for its empty fields
+  fn excluded_by_cage(&self, cage: &Cage) -> Flags {
+    let in_cage = cage.cells.iter()
+      .fold(Flags::empty(), |acc, other| acc.set(self.at(*other)));
+    let filled_sum: u32 = cage.cells.iter().map(|other| self.at(*other) as u32).sum();
+    let empty_count = cage.cells.iter().filter(|other| self.at(**other) == 0).count();
+    if empty_count == 0 {
+      return in_cage;
+    }
+    let free = in_cage.inverse(self.board_size() as u8);
+
+    free.to_vec().into_iter()
+      .filter(|digit| {
+        let rest = (cage.sum as i64) - (filled_sum as i64) - (*digit as i64);
+        !Sudoku::can_sum(free.unset(*digit), empty_count - 1, rest)
+      })
+      .fold(in_cage, |acc, digit| acc.set(digit))
+  }
+
+  /// Checks whether `count` distinct digits taken from `digits` add up to `sum`
+  fn can_sum(digits: Flags, count: usize, sum: i64) -> bool {
+    if count == 0 {
+      return sum == 0;
+    }
+    digits.to_vec().into_iter()
+      .any(|digit| {
+        let larger = digits.to_vec().into_iter()
+          .filter(|d| *d > digit)
+          .fold(Flags::empty(), |acc, d| acc.set(d));
+        sum >= digit as i64 && Sudoku::can_sum(larger, count - 1, sum - digit as i64)
+      })
+  }
+
+  /// Digits of the unit are exactly 1 to `board_size`, so neither an empty field, a
+  /// repeated digit nor a digit too large for the board slips through
+  fn is_unit_complete(&self, unit: Vec<Position>) -> bool {
+    let used = unit.into_iter()
+      .fold(Flags::empty(), |acc, pos| acc.set(self.at(pos)));
+    used.bits() == (1 << self.board_size()) - 1
+  }
+
+  /// Checks that the row holds every digit exactly once
+  pub fn is_row_complete(&self, y: usize) -> bool {
+    self.is_unit_complete(self.row_positions(y))
+  }
+
+  /// Checks that the column holds every digit exactly once
+  pub fn is_column_complete(&self, x: usize) -> bool {
+    self.is_unit_complete(self.column_positions(x))
+  }
+
+  /// Checks that the box at given box coordinates holds every digit exactly once
+  pub fn is_box_complete(&self, box_pos: Position) -> bool {
+    self.is_unit_complete(self.cells_in_box(box_pos))
+  }
+
+  /// Checks that every field is filled and no rule is broken, every row, column, box
+  /// and extra region holds each digit exactly once
   pub fn is_solved(&self) -> bool {
     let all_rows_correct = (0 .. self.board_size())
-      .all(|y| self.used_in_row(y).size() == self.board_size() as u8);
+      .all(|y| self.is_row_complete(y));
     let all_columns_correct = (0 .. self.board_size())
-      .all(|x| self.used_in_column(x).size() == self.board_size() as u8);
+      .all(|x| self.is_column_complete(x));
     let all_boxes_correct = (0 .. self.box_size())
       .flat_map(|x| (0 .. self.box_size()).map(move |y| (x, y)))
-      .all(|pos| self.used_in_box(pos).size() == self.board_size() as u8);
+      .all(|box_pos| self.is_box_complete(box_pos));
 
-      all_rows_correct && all_columns_correct && all_boxes_correct
+    let all_cages_correct = self.cages.iter()
+      .all(|cage| !self.is_cage_broken(cage));
+    let knights_correct = !self.anti_knight || self.iter()
+      .all(|(digit, pos)| self.knight_peers(pos).into_iter().all(|peer| self.at(peer) != digit));
+    let windoku_correct = self.windoku_regions().into_iter()
+      .all(|region| self.is_unit_complete(region));
+
+      all_rows_correct && all_columns_correct && all_boxes_correct && all_cages_correct && knights_correct
+        && windoku_correct
   }
 
   pub fn used_in_row(&self, y: usize) -> Flags {
@@ -136,6 +852,11 @@ impl Sudoku {
   }
 
   pub fn used_in_box(&self, (pos_x, pos_y): Position) -> Flags {
+    if self.regions.is_some() {
+      return self.box_positions((pos_x, pos_y)).into_iter()
+        .fold(Flags::empty(), |used, pos| used.set(self.at(pos)));
+    }
+
     let box_offset_x = (pos_x / self.size as usize) * self.size as usize;
     let box_offset_y = (pos_y / self.size as usize) * self.size as usize;
     let mut used = Flags::empty();
@@ -147,11 +868,25 @@ impl Sudoku {
     used
   }
 
+  /// Digits ruled out for the field by its row, column, box, killer cages, knight
+  /// peers on anti-knight boards and its windoku region
   pub fn used(&self, (pos_x, pos_y): Position) -> Flags {
     let used_in_row = self.used_in_row(pos_y);
     let used_in_column = self.used_in_column(pos_x);
     let used_in_box = self.used_in_box((pos_x, pos_y));
-    used_in_row.or(used_in_column).or(used_in_box)
+    let used_by_knights = match self.anti_knight {
+      true => self.knight_peers((pos_x, pos_y)).into_iter()
+        .fold(Flags::empty(), |used, pos| used.set(self.at(pos))),
+      false => Flags::empty()
+    };
+    let used_in_windoku = self.windoku_region_of((pos_x, pos_y)).unwrap_or_default().into_iter()
+      .fold(Flags::empty(), |used, pos| used.set(self.at(pos)));
+    let used_by_rules = used_in_row.or(used_in_column).or(used_in_box).or(used_by_knights).or(used_in_windoku);
+    self.cages.iter()
+      .filter(|cage| cage.cells.contains(&(pos_x, pos_y)))
+      .fold(used_by_rules, |acc, cage| {
+        acc.or(self.excluded_by_cage(cage))
+      })
   }
 
   pub fn available(&self, pos: Position) -> Flags {
@@ -163,6 +898,163 @@ impl Sudoku {
     }
   }
 
+  /// Panics at the first empty field whose candidates don't match `used` or contain
+  /// a digit of some peer, and at the first pair of clashing filled fields
+  #[cfg(debug_assertions)]
+  pub fn debug_assert_consistent(&self) {
+    for (_, pos) in self.iter().filter(|(digit, _)| *digit == 0) {
+      let available = self.available(pos);
+      assert!(available == self.used(pos).inverse(self.board_size() as u8),
+        "candidates of {:?} don't match used digits", pos);
+      let clashing_peer = self.peers(pos).into_iter()
+        .find(|peer| self.at(*peer) != 0 && available.contains(self.at(*peer)));
+      if let Some(peer) = clashing_peer {
+        panic!("candidates of {:?} contain {} from {:?}", pos, self.at(peer), peer);
+      }
+    }
+    if let Some((first, second)) = self.conflicts().into_iter().next() {
+      panic!("{:?} and {:?} hold the same digit in one unit", first, second);
+    }
+  }
+
+  /// Candidates of the field as if the listed fields were empty, so mistakes of the
+  /// player don't hide digits from the rest of the board
+  pub fn available_ignoring(&self, pos: Position, ignore: &[Position]) -> Flags {
+    ignore.iter()
+      .fold(self.clone(), |board, ignored| board.set(*ignored, 0))
+      .available(pos)
+  }
+
+  fn row_positions(&self, y: usize) -> Vec<Position> {
+    (0 .. self.board_size()).map(|x| (x, y)).collect()
+  }
+
+  fn column_positions(&self, x: usize) -> Vec<Position> {
+    (0 .. self.board_size()).map(|y| (x, y)).collect()
+  }
+
+  /// Fields of the box with given number, in reading order
+  fn region_positions(&self, index: usize) -> Vec<Position> {
+    match &self.regions {
+      Some(regions) => (0 .. self.board_size())
+        .flat_map(|y| (0 .. self.board_size()).map(move |x| (x, y)))
+        .filter(|(x, y)| regions[y * self.board_size() + x] == index)
+        .collect(),
+      None => {
+        let box_offset_x = (index % self.box_size()) * self.box_size();
+        let box_offset_y = (index / self.box_size()) * self.box_size();
+        (0 .. self.board_size())
+          .map(|i| (box_offset_x + i % self.box_size(), box_offset_y + i / self.box_size()))
+          .collect()
+      }
+    }
+  }
+
+  /// Fields of the box containing given position, in reading order
+  fn box_positions(&self, pos: Position) -> Vec<Position> {
+    self.region_positions(self.box_index(pos))
+  }
+
+  /// Number of the box holding given field, classic boxes are numbered in reading
+  /// order and jigsaw regions keep their own numbers
+  pub fn box_index(&self, (x, y): Position) -> usize {
+    match &self.regions {
+      Some(regions) => regions[y * self.board_size() + x],
+      None => (y / self.box_size()) * self.box_size() + x / self.box_size()
+    }
+  }
+
+  /// Fields of the box at given box coordinates, in reading order. Jigsaw region
+  /// `n` sits at the coordinates classic box `n` would have.
+  pub fn cells_in_box(&self, (box_x, box_y): Position) -> Vec<Position> {
+    self.region_positions(box_y * self.box_size() + box_x)
+  }
+
+  /// Checks whether two different fields share a row, column, box or windoku region,
+  /// or are a knight's move apart on anti-knight boards
+  pub fn sees(&self, (x1, y1): Position, (x2, y2): Position) -> bool {
+    let same_box = self.box_index((x1, y1)) == self.box_index((x2, y2));
+    let knight_move = self.anti_knight
+      && matches!((x1.max(x2) - x1.min(x2), y1.max(y2) - y1.min(y2)), (1, 2) | (2, 1));
+    let same_windoku = self.windoku
+      && self.windoku_region_of((x1, y1)).is_some_and(|region| region.contains(&(x2, y2)));
+    (x1, y1) != (x2, y2) && (x1 == x2 || y1 == y2 || same_box || knight_move || same_windoku)
+  }
+
+  /// Fields seen from given position as `sees` tells, in reading order
+  pub fn peers(&self, pos: Position) -> Vec<Position> {
+    (0 .. self.board_size())
+      .flat_map(|y| (0 .. self.board_size()).map(move |x| (x, y)))
+      .filter(|other| self.sees(pos, *other))
+      .collect()
+  }
+
+  /// All rows, then all columns, then all boxes as lists of positions
+  pub fn units(&self) -> Vec<Vec<Position>> {
+    let rows = (0 .. self.board_size())
+      .map(|y| self.row_positions(y));
+    let columns = (0 .. self.board_size())
+      .map(|x| self.column_positions(x));
+    let boxes = (0 .. self.box_size())
+      .flat_map(|x| (0 .. self.box_size()).map(move |y| (x, y)))
+      .map(|box_pos| self.cells_in_box(box_pos));
+    rows.chain(columns).chain(boxes).collect()
+  }
+
+  /// Pairs of filled fields which share a unit or windoku region, or are a knight's
+  /// move apart on anti-knight boards, and hold the same digit
+  pub fn conflicts(&self) -> Vec<(Position, Position)> {
+    let knight_pairs = self.iter()
+      .filter(|_| self.anti_knight)
+      .flat_map(|(_, pos)| self.knight_peers(pos).into_iter()
+        .filter(move |peer| pos < *peer)
+        .map(move |peer| (pos, peer)));
+    self.units().into_iter()
+      .chain(self.windoku_regions())
+      .flat_map(|unit| unit.into_iter()
+        .filter(|pos| self.at(*pos) != 0)
+        .tuple_combinations()
+        .collect::<Vec<_>>())
+      .chain(knight_pairs)
+      .filter(|(p1, p2)| self.at(*p1) != 0 && self.at(*p1) == self.at(*p2))
+      .unique()
+      .collect()
+  }
+
+  /// Sorted candidates of every field indexed as `[y][x]`, filled fields have none
+  pub fn candidate_grid(&self) -> Vec<Vec<Vec<u8>>> {
+    (0 .. self.board_size())
+      .map(|y| (0 .. self.board_size())
+        .map(|x| match self.at((x, y)) {
+          0 => self.available((x, y)).to_vec(),
+          _ => vec![]
+        })
+        .collect())
+      .collect()
+  }
+
+  fn empty_candidates(&self, unit: Vec<Position>) -> Vec<(Position, Flags)> {
+    unit.into_iter()
+      .filter(|pos| self.at(*pos) == 0)
+      .map(|pos| (pos, self.available(pos)))
+      .collect()
+  }
+
+  /// Candidates of empty fields in the row, from left to right
+  pub fn row_candidates(&self, y: usize) -> Vec<(Position, Flags)> {
+    self.empty_candidates(self.row_positions(y))
+  }
+
+  /// Candidates of empty fields in the column, from top to bottom
+  pub fn column_candidates(&self, x: usize) -> Vec<(Position, Flags)> {
+    self.empty_candidates(self.column_positions(x))
+  }
+
+  /// Candidates of empty fields in the box at given box coordinates, in reading order
+  pub fn box_candidates(&self, box_pos: Position) -> Vec<(Position, Flags)> {
+    self.empty_candidates(self.cells_in_box(box_pos))
+  }
+
   pub fn iter<'t>(&'t self) -> impl Iterator<Item=(u8, (usize, usize))> + 't {
     (0 .. self.board_size())
       .flat_map(move |x| (0 .. self.board_size()).map(move |y| (x, y)))
@@ -181,9 +1073,8 @@ impl Sudoku {
       .map(move |pos| self.available(pos))
   }
 
-  pub fn iter_box_avail<'t>(&'t self, (x, y): (usize, usize)) -> impl Iterator<Item=Flags> + 't {
-    (x * self.box_size() .. (x + 1) * self.box_size())
-      .flat_map(move |x| (y * self.box_size() .. (y + 1) * self.box_size()).map(move |y| (x, y)))
+  pub fn iter_box_avail<'t>(&'t self, box_pos: (usize, usize)) -> impl Iterator<Item=Flags> + 't {
+    self.cells_in_box(box_pos).into_iter()
       .map(move |pos| self.available(pos))
   }
 }
@@ -220,4 +1111,677 @@ mod test {
     println!("{}", Sudoku::load(data.as_str(), 2).pretty_print());
     assert_eq!(data, Sudoku::load(data.as_str(), 2).save())
   }
+
+  #[test]
+  fn unit_completion() {
+    let solved = Sudoku::load("1234341221434321", 2);
+    assert!((0 .. 4).all(|i| solved.is_row_complete(i) && solved.is_column_complete(i)));
+    assert!((0 .. 4).all(|i| solved.is_box_complete((i % 2, i / 2))));
+
+    let partial = solved.clear((1, 2));
+    assert!(partial.is_row_complete(0));
+    assert!(!partial.is_row_complete(2));
+    assert!(partial.is_column_complete(0));
+    assert!(!partial.is_column_complete(1));
+    assert!(partial.is_box_complete((1, 1)));
+    assert!(!partial.is_box_complete((0, 1)));
+
+    let latin_square = Sudoku::load("1234234134124123", 2);
+    assert!((0 .. 4).all(|i| latin_square.is_row_complete(i) && latin_square.is_column_complete(i)));
+    assert!(!latin_square.is_box_complete((0, 0)));
+    assert!(!latin_square.is_solved());
+  }
+
+  #[test]
+  fn filled_but_invalid_boards() {
+    assert!(!Sudoku::load(&"1".repeat(16), 2).is_solved());
+    assert!(!Sudoku::load(&"1".repeat(81), 3).is_solved());
+
+    let solved = Sudoku::load("1234341221434321", 2);
+    assert!(solved.is_solved());
+    let swapped = solved.clone().set((0, 0), 2).set((1, 0), 1);
+    assert!(!swapped.is_solved());
+    let too_large = solved.iter()
+      .fold(solved.clone(), |board, (digit, pos)| board.set(pos, if digit == 4 { 5 } else { digit }));
+    assert!(!too_large.is_row_complete(0));
+    assert!(!too_large.is_solved());
+  }
+
+  #[test]
+  fn completion_progress() {
+    let data 
+      = "".to_string()
+      + "  8" + "  3" + "461"
+      + "2 6" + " 84" + "   "
+      + "3  " + "  7" + " 9 "
+
+      + " 3 " + "75 " + "68 "
+      + " 87" + " 1 " + "   "
+      + " 5 " + " 4 " + "13 "
+
+      + "  9" + "27 " + "31 "
+      + "   " + "   " + "   "
+      + "763" + "4 1" + "8 2";
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    let clues = data.chars().filter(|c| *c != ' ').count();
+    assert_eq!(81 - clues, sudoku.remaining_cells());
+
+    let solved 
+      = "1234".to_string()
+      + "3412"
+      + "2143"
+      + "4321";
+    assert_eq!(1.0, Sudoku::load(solved.as_str(), 2).completion_fraction());
+    assert_eq!(0.0, Sudoku::empty(2).completion_fraction());
+  }
+
+  #[test]
+  #[cfg(debug_assertions)]
+  fn loaded_boards_consistent() {
+    let medium 
+      = "".to_string()
+      + "  8" + "  3" + "461"
+      + "2 6" + " 84" + "   "
+      + "3  " + "  7" + " 9 "
+
+      + " 3 " + "75 " + "68 "
+      + " 87" + " 1 " + "   "
+      + " 5 " + " 4 " + "13 "
+
+      + "  9" + "27 " + "31 "
+      + "   " + "   " + "   "
+      + "763" + "4 1" + "8 2";
+    Sudoku::load(medium.as_str(), 3).debug_assert_consistent();
+    Sudoku::load("12  341 2 43    ", 2).debug_assert_consistent();
+    Sudoku::empty(3).debug_assert_consistent();
+    Sudoku::empty(2).with_cages(vec![Cage { cells: vec![(0, 0), (1, 0)], sum: 3 }]).debug_assert_consistent();
+  }
+
+  #[test]
+  #[cfg(debug_assertions)]
+  #[should_panic(expected = "(0, 0) and (1, 0) hold the same digit in one unit")]
+  fn clashing_board_inconsistent() {
+    Sudoku::load("11              ", 2).debug_assert_consistent();
+  }
+
+  #[test]
+  fn pretty_grid_lines() {
+    let medium 
+      = "".to_string()
+      + "  8" + "  3" + "461"
+      + "2 6" + " 84" + "   "
+      + "3  " + "  7" + " 9 "
+
+      + " 3 " + "75 " + "68 "
+      + " 87" + " 1 " + "   "
+      + " 5 " + " 4 " + "13 "
+
+      + "  9" + "27 " + "31 "
+      + "   " + "   " + "   "
+      + "763" + "4 1" + "8 2";
+    let sudoku = Sudoku::load(medium.as_str(), 3);
+    let pretty = sudoku.pretty();
+    let lines: Vec<&str> = pretty.lines().collect();
+    assert_eq!(13, lines.len());
+    for (index, line) in lines.iter().enumerate() {
+      if index % 4 == 0 {
+        assert_eq!("+---+---+---+", *line);
+      } else {
+        assert_eq!(13, line.len());
+      }
+    }
+    assert_eq!("|..8|..3|461|", lines[1]);
+    assert_eq!("|...|...|...|", lines[10]);
+    assert_eq!(Ok(sudoku), Sudoku::load_pretty(&pretty, 3));
+    assert_eq!("+--+--+\n|..|..|\n|..|..|\n+--+--+\n|..|..|\n|..|..|\n+--+--+\n", Sudoku::empty(2).pretty());
+  }
+
+  #[test]
+  fn load_pretty_grid() {
+    let grid = "".to_string()
+      + "+--+--+\n"
+      + "|12|  |\n"
+      + "|34|1.|\n"
+      + "+--+--+\n"
+      + "|2 |43|\n"
+      + "|  |  |\r\n"
+      + "+--+--+\n";
+    assert_eq!(Ok(Sudoku::load("12  341 2 43    ", 2)), Sudoku::load_pretty(&grid, 2));
+    assert_eq!(Err(LoadError::WrongLength { expected: 16, found: 12 }),
+      Sudoku::load_pretty("|12|  |\n|34|1.|\n|2 |43|", 2));
+  }
+
+  #[test]
+  fn unsupported_box_sizes() {
+    assert_eq!(Err(LoadError::UnsupportedBoxSize(0)), Sudoku::load_checked("", 0));
+    assert_eq!(Err(LoadError::UnsupportedBoxSize(0)), Sudoku::from_grid(&[], 0));
+    assert_eq!(Err(LoadError::UnsupportedBoxSize(6)), Sudoku::load_strict(&" ".repeat(36 * 36), 6));
+    assert_eq!(Err(LoadError::UnsupportedBoxSize(200)), Sudoku::load_checked("1", 200));
+    assert!(Sudoku::check_box_size(MAX_BOX_SIZE).is_ok());
+    assert_eq!("box size 6 is not between 1 and 5", LoadError::UnsupportedBoxSize(6).to_string());
+  }
+
+  #[test]
+  #[should_panic(expected = "box size 0 is not between 1 and 5")]
+  fn load_zero_box_size() {
+    Sudoku::load("", 0);
+  }
+
+  #[test]
+  fn empty_board() {
+    let sudoku = Sudoku::empty(3);
+    assert_eq!(81, sudoku.remaining_cells());
+    assert_eq!(9, sudoku.board_size());
+    assert!(!sudoku.is_solved());
+    assert!(sudoku.iter().all(|(digit, _)| digit == 0));
+    assert_eq!(16, Sudoku::empty(2).remaining_cells());
+  }
+
+  #[test]
+  fn get_fields() {
+    let sudoku = Sudoku::load("12  341 2 43   1", 2);
+    assert_eq!(1, sudoku.get((0, 0)));
+    assert_eq!(4, sudoku.get((2, 2)));
+    assert_eq!(0, sudoku.get((3, 0)));
+    assert_eq!(0, sudoku.get((1, 3)));
+  }
+
+  #[test]
+  #[should_panic(expected = "position (4, 0) is outside of 4x4 board")]
+  fn get_out_of_range() {
+    Sudoku::empty(2).get((4, 0));
+  }
+
+  #[test]
+  fn compare_solutions() {
+    let solved = Sudoku::load("1234341221434321", 2);
+    let mistaken = solved.clone().set((2, 1), 2);
+    assert!(solved.same_solution(&solved.clone().with_cages(vec![Cage { cells: vec![(0, 0)], sum: 1 }])));
+    assert!(!solved.same_solution(&mistaken));
+    assert!(!solved.same_solution(&Sudoku::empty(3)));
+    assert_eq!(vec![(2, 1)], solved.differing_cells(&mistaken));
+    assert!(solved.differing_cells(&solved).is_empty());
+  }
+
+  #[test]
+  fn puzzle_from_solution_and_mask() {
+    let lock = crate::abort_lock::AbortLock::prepare();
+    let solved = Sudoku::load("1234341221434321", 2);
+    let keep = [(2, 0), (3, 0), (0, 1), (3, 1), (1, 2), (0, 3), (2, 3)];
+    let puzzle = Sudoku::from_solution_and_mask(&solved, &keep);
+    assert_eq!(keep.len(), puzzle.iter().filter(|(digit, _)| *digit != 0).count());
+    assert!(keep.iter().all(|pos| puzzle.at(*pos) == solved.at(*pos)));
+    assert!(crate::solver::has_unique_solution(&puzzle, &lock));
+    assert_eq!(Some(solved.clone()), crate::solver::solution(puzzle, &lock));
+    assert_eq!(Sudoku::empty(2), Sudoku::from_solution_and_mask(&solved, &[]));
+  }
+
+  #[test]
+  #[should_panic(expected = "solved board")]
+  fn mask_of_unsolved_board() {
+    Sudoku::from_solution_and_mask(&Sudoku::load("12  341 2 43    ", 2), &[(0, 0)]);
+  }
+
+  #[test]
+  fn diff_of_changed_board() {
+    let sudoku = Sudoku::load("12  341 2 43    ", 2);
+    let changed = sudoku.clone().set((3, 3), 2).set((0, 1), 0);
+    assert_eq!(vec![((0, 1), 3, 0), ((3, 3), 0, 2)], sudoku.diff(&changed));
+    assert_eq!(vec![((0, 1), 0, 3), ((3, 3), 2, 0)], changed.diff(&sudoku));
+    assert!(sudoku.diff(&sudoku).is_empty());
+  }
+
+  #[test]
+  #[should_panic(expected = "only boards of the same size can be compared")]
+  fn diff_of_boards_with_different_sizes() {
+    Sudoku::empty(2).differing_cells(&Sudoku::empty(3));
+  }
+
+  #[test]
+  fn scarcest_digit_on_partial_board() {
+    let sudoku = Sudoku::load("12  341 2 43    ", 2);
+    assert_eq!(Some((3, 2)), sudoku.scarcest_digit());
+    assert_eq!(Some((1, 4)), Sudoku::empty(2).scarcest_digit());
+    assert_eq!(None, Sudoku::load("1234341221434321", 2).scarcest_digit());
+  }
+
+  #[test]
+  fn candidate_stats_test() {
+    assert_eq!((729, 9, 9.0), Sudoku::empty(3).candidate_stats());
+    assert_eq!((0, 0, 0.0), Sudoku::load("1234341221434321", 2).candidate_stats());
+
+    let (total, max, average) = Sudoku::empty(2).set((0, 0), 1).candidate_stats();
+    assert_eq!((15 * 4 - 7, 4), (total, max));
+    assert!((average - 53.0 / 15.0).abs() < 1e-6);
+  }
+
+  #[test]
+  fn candidate_grid_test() {
+    let data 
+      = "   3".to_string()
+      + "3  2"
+      + "2  1"
+      + "1   ";
+    let grid = Sudoku::load(data.as_str(), 2).candidate_grid();
+    assert_eq!(vec![1, 2, 4], grid[0][1]);
+    assert_eq!(vec![1, 4], grid[1][1]);
+    assert!(grid[0][3].is_empty());
+  }
+
+  #[test]
+  fn unit_candidates() {
+    let data 
+      = "   3".to_string()
+      + "3  2"
+      + "2  1"
+      + "1   ";
+    let sudoku = Sudoku::load(data.as_str(), 2);
+    let flags = Flags::from_vec;
+
+    assert_eq!(
+      vec![((1, 1), flags(vec![1, 4])), ((2, 1), flags(vec![1, 4]))],
+      sudoku.row_candidates(1));
+    assert_eq!(vec![((0, 0), flags(vec![4]))], sudoku.column_candidates(0));
+    assert_eq!(
+      vec![((2, 2), flags(vec![3, 4])), ((2, 3), flags(vec![2, 3, 4])), ((3, 3), flags(vec![4]))],
+      sudoku.box_candidates((1, 1)));
+  }
+
+  #[test]
+  fn sees_test() {
+    let sudoku = Sudoku::empty(3);
+    assert!(sudoku.sees((0, 0), (8, 0)));
+    assert!(sudoku.sees((4, 1), (4, 7)));
+    assert!(sudoku.sees((3, 3), (5, 5)));
+    assert!(!sudoku.sees((2, 2), (3, 3)));
+    assert!(!sudoku.sees((4, 4), (4, 4)));
+  }
+
+  #[test]
+  fn box_membership() {
+    let sudoku = Sudoku::empty(3);
+    assert_eq!(0, sudoku.box_index((2, 2)));
+    assert_eq!(1, sudoku.box_index((3, 0)));
+    assert_eq!(4, sudoku.box_index((4, 4)));
+    assert_eq!(5, sudoku.box_index((8, 3)));
+    assert_eq!(6, sudoku.box_index((0, 8)));
+    assert_eq!(8, sudoku.box_index((6, 6)));
+    assert_eq!(vec![(3, 6), (4, 6), (5, 6), (3, 7), (4, 7), (5, 7), (3, 8), (4, 8), (5, 8)],
+      sudoku.cells_in_box((1, 2)));
+
+    let small = Sudoku::empty(2);
+    assert_eq!(0, small.box_index((1, 1)));
+    assert_eq!(1, small.box_index((2, 0)));
+    assert_eq!(2, small.box_index((1, 2)));
+    assert_eq!(3, small.box_index((3, 3)));
+    assert_eq!(vec![(2, 0), (3, 0), (2, 1), (3, 1)], small.cells_in_box((1, 0)));
+    assert!((0 .. 2).flat_map(|x| (0 .. 2).map(move |y| (x, y)))
+      .all(|box_pos| small.cells_in_box(box_pos).iter()
+        .all(|pos| small.box_index(*pos) == box_pos.1 * 2 + box_pos.0)));
+  }
+
+  #[test]
+  fn digits_by_unit() {
+    let solved = Sudoku::load("1234341221434321", 2);
+    assert!(solved.rows().iter().all(|row| row.iter().copied().sorted().eq(1 ..= 4)));
+    assert_eq!(vec![1, 3, 2, 4], solved.columns()[0]);
+    assert_eq!(vec![vec![1, 2, 3, 4], vec![3, 4, 1, 2], vec![2, 1, 4, 3], vec![4, 3, 2, 1]], solved.boxes());
+
+    let sudoku = Sudoku::empty(3).set((4, 1), 7);
+    assert_eq!(7, sudoku.rows()[1][4]);
+    assert_eq!(7, sudoku.columns()[4][1]);
+    assert_eq!(7, sudoku.boxes()[1][4]);
+    assert_eq!(1, sudoku.boxes().iter().flatten().filter(|d| **d != 0).count());
+  }
+
+  #[test]
+  fn anti_knight_rule() {
+    let lock = crate::abort_lock::AbortLock::prepare();
+    let sudoku = Sudoku::load(" 4      3    1  ", 2).with_anti_knight();
+    assert_eq!(vec![(2, 1), (1, 2)], sudoku.knight_peers((0, 0)));
+    assert_eq!(8, Sudoku::empty(3).knight_peers((4, 4)).len());
+    assert!(!sudoku.available((3, 1)).contains(4));
+    assert!(Sudoku::load(" 4      3    1  ", 2).available((3, 1)).contains(4));
+
+    let solutions = crate::solver::solution_iter(sudoku.clone(), &lock).collect::<Vec<_>>();
+    assert_eq!(vec!["1432234132144123".to_string()], solutions.iter().map(|s| s.save()).collect::<Vec<_>>());
+    assert!(solutions[0].is_solved() && solutions[0].is_anti_knight());
+    assert_eq!(6, crate::solver::count_solutions(Sudoku::load(" 4      3    1  ", 2), 10, &lock));
+
+    let clashing = Sudoku::empty(2).with_anti_knight().set((0, 0), 1).set((2, 1), 1);
+    assert_eq!(vec![((0, 0), (2, 1))], clashing.conflicts());
+    assert!(crate::solver::is_unsolvable(&clashing));
+  }
+
+  #[test]
+  fn windoku_rule() {
+    let lock = crate::abort_lock::AbortLock::prepare();
+    let data 
+      = "".to_string()
+      + "1  " + "  6" + "  9"
+      + "  6" + "   " + "   "
+      + "78 " + " 2 " + "45 "
+
+      + "  4" + " 97" + " 6 "
+      + "217" + "86 " + " 9 "
+      + "   " + "3  " + " 7 "
+
+      + "3  " + " 7 " + "   "
+      + " 7 " + " 42" + "   "
+      + " 6 " + "9  " + " 47";
+    let sudoku = Sudoku::load(data.as_str(), 3).with_windoku();
+    assert_eq!(4, sudoku.windoku_regions().len());
+    assert_eq!((1, 5), sudoku.windoku_regions()[2][0]);
+    assert!(sudoku.sees((1, 1), (3, 3)));
+    assert!(!Sudoku::load(data.as_str(), 3).sees((1, 1), (3, 3)));
+
+    let solutions: Vec<Sudoku> = crate::solver::solution_iter(sudoku.clone(), &lock).take(2).collect();
+    assert_eq!(1, solutions.len());
+    assert!(solutions[0].is_solved() && solutions[0].is_windoku());
+    assert!(solutions[0].windoku_regions().into_iter()
+      .all(|region| region.iter().map(|pos| solutions[0].at(*pos)).sorted().eq(1 ..= 9)));
+    assert_eq!(2, crate::solver::count_solutions(Sudoku::load(data.as_str(), 3), 2, &lock));
+
+    let clashing = Sudoku::empty(3).with_windoku().set((1, 1), 4).set((3, 3), 4);
+    assert_eq!(vec![((1, 1), (3, 3))], clashing.conflicts());
+    assert!(Sudoku::empty(3).windoku_regions().is_empty());
+  }
+
+  #[test]
+  fn jigsaw_regions() {
+    let regions = vec![
+      0, 0, 0, 1,
+      2, 0, 1, 1,
+      2, 2, 3, 1,
+      2, 3, 3, 3,
+    ];
+    let sudoku = Sudoku::empty(2).with_regions(regions.clone()).unwrap();
+    assert_eq!(vec![(3, 0), (2, 1), (3, 1), (3, 2)], sudoku.cells_in_box((1, 0)));
+    assert_eq!(2, sudoku.box_index((0, 3)));
+    assert!(!sudoku.clone().set((2, 0), 1).available((1, 1)).contains(1));
+    assert!(Sudoku::empty(2).set((2, 0), 1).available((1, 1)).contains(1));
+
+    let solved = crate::solver::solution(sudoku.clone(), &crate::abort_lock::AbortLock::prepare()).unwrap();
+    assert!(solved.is_solved());
+    assert!((0 .. 4).all(|region| solved.cells_in_box((region % 2, region / 2)).iter()
+      .map(|pos| solved.at(*pos))
+      .sorted()
+      .eq(1 ..= 4)));
+    assert_eq!(Some(&regions[..]), solved.regions());
+    assert_eq!(sudoku.rotate90().rotate90().rotate90().rotate90(), sudoku);
+
+    let mut uneven = regions;
+    uneven[0] = 1;
+    assert_eq!(Err(LoadError::WrongRegionSize { region: 0, expected: 4, found: 3 }),
+      Sudoku::empty(2).with_regions(uneven));
+  }
+
+  #[test]
+  fn peers_test() {
+    let sudoku = Sudoku::empty(3);
+    assert_eq!(20, sudoku.peers((4, 4)).len());
+    assert_eq!(7, Sudoku::empty(2).peers((1, 2)).len());
+
+    let corner: Vec<Position> = (1 .. 9).map(|x| (x, 0))
+      .chain(vec![(1, 1), (2, 1), (1, 2), (2, 2)])
+      .chain((1 .. 9).map(|y| (0, y)))
+      .sorted_by_key(|(x, y)| (*y, *x))
+      .collect();
+    assert_eq!(corner, sudoku.peers((0, 0)));
+  }
+
+  #[test]
+  fn try_set_legal() {
+    let data 
+      = "   3".to_string()
+      + "3  2"
+      + "2  1"
+      + "1   ";
+    let sudoku = Sudoku::load(data.as_str(), 2);
+    assert_eq!(Ok(sudoku.clone().set((1, 0), 1)), sudoku.try_set((1, 0), 1));
+  }
+
+  #[test]
+  fn try_set_row_conflict() {
+    let data 
+      = "   3".to_string()
+      + "3  2"
+      + "2  1"
+      + "1   ";
+    let sudoku = Sudoku::load(data.as_str(), 2);
+    assert_eq!(Err(PlacementError::InRow((3, 0))), sudoku.try_set((1, 0), 3));
+    assert_eq!(Err(PlacementError::InColumn((0, 2))), sudoku.try_set((0, 0), 2));
+  }
+
+  #[test]
+  fn try_set_out_of_range() {
+    let sudoku = Sudoku::empty(2);
+    assert_eq!(Err(PlacementError::DigitOutOfRange(5)), sudoku.try_set((1, 0), 5));
+    assert_eq!(Err(PlacementError::DigitOutOfRange(0)), sudoku.try_set((1, 0), 0));
+  }
+
+  #[test]
+  fn clear_restores_candidates() {
+    let sudoku = Sudoku::empty(3).set((4, 4), 5).set((0, 0), 5);
+    let cleared = sudoku.clear((4, 4));
+    assert_eq!(sudoku.remaining_cells() + 1, cleared.remaining_cells());
+    assert!(!sudoku.available((4, 2)).contains(5));
+    assert!(cleared.available((4, 2)).contains(5));
+    assert!(cleared.available((3, 3)).contains(5));
+    assert!(!cleared.available((0, 4)).contains(5));
+
+    let conflicted = sudoku.clone().set((4, 1), 5);
+    assert_eq!(1, conflicted.conflicts().len());
+    assert!(conflicted.clear((4, 4)).conflicts().is_empty());
+  }
+
+  #[test]
+  fn available_ignoring_conflicts() {
+    let sudoku = Sudoku::empty(3).set((0, 0), 5).set((8, 0), 5).set((4, 4), 7);
+    let ignore: Vec<Position> = sudoku.conflicts().into_iter()
+      .flat_map(|(p1, p2)| vec![p1, p2])
+      .collect();
+
+    assert!(!sudoku.available((4, 0)).contains(5));
+    assert!(sudoku.available_ignoring((4, 0), &ignore).contains(5));
+    assert!(!sudoku.available_ignoring((4, 0), &ignore).contains(7));
+    assert_eq!(sudoku.available((4, 8)), sudoku.available_ignoring((4, 8), &[]));
+  }
+
+  #[test]
+  fn cage_limits_candidates() {
+    let sudoku = Sudoku::empty(3).set((0, 0), 1)
+      .with_cages(vec![Cage { cells: vec![(0, 0), (1, 0), (2, 0)], sum: 6 }]);
+    assert_eq!(Flags::from_vec(vec![2, 3]), sudoku.available((1, 0)));
+    assert_eq!(Flags::from_vec(vec![3]), sudoku.clone().set((1, 0), 2).available((2, 0)));
+    assert!(sudoku.clone().set((1, 0), 2).set((2, 0), 4).is_cage_broken(&sudoku.cages()[0]));
+
+    let filled_cage = Sudoku::empty(3).set((0, 0), 1).set((1, 0), 2)
+      .with_cages(vec![Cage { cells: vec![(0, 0), (1, 0)], sum: 3 }]);
+    assert_eq!(Flags::from_vec(vec![1, 2]), filled_cage.used((0, 0)));
+  }
+
+  #[test]
+  fn canonical_form_ignores_shuffling() {
+    let data 
+      = "1  4".to_string()
+      + "    "
+      + "    "
+      + " 32 ";
+    let sudoku = Sudoku::load(data.as_str(), 2);
+    let canonical = sudoku.canonical_form().unwrap();
+
+    assert_eq!(Some(canonical.clone()), crate::generator::shuffle(sudoku.clone(), 7).canonical_form());
+    assert_eq!(Some(canonical.clone()), crate::generator::shuffle(sudoku, 8).canonical_form());
+    assert_eq!(Some(canonical.clone()), canonical.canonical_form());
+
+    assert_eq!(None, Sudoku::empty(4).canonical_form());
+    let regions = vec![0, 0, 0, 1, 2, 0, 1, 1, 2, 2, 3, 1, 2, 3, 3, 3];
+    assert_eq!(None, canonical.with_regions(regions).unwrap().canonical_form());
+  }
+
+  #[test]
+  fn geometric_transforms() {
+    let data 
+      = "1  4".to_string()
+      + "    "
+      + "    "
+      + " 32 ";
+    let lock = crate::abort_lock::AbortLock::prepare();
+    let sudoku = Sudoku::load(data.as_str(), 2)
+      .with_cages(vec![Cage { cells: vec![(0, 0), (1, 0)], sum: 3 }]);
+    let solved = Sudoku::load("1234341221434321", 2);
+
+    assert_eq!(sudoku, sudoku.rotate90().rotate90().rotate90().rotate90());
+    assert_eq!(sudoku, sudoku.transpose().transpose());
+    assert_eq!(sudoku, sudoku.mirror_horizontal().mirror_horizontal());
+    assert_eq!(sudoku.rotate90(), sudoku.transpose().mirror_horizontal());
+    assert_eq!("   13   2      4", sudoku.rotate90().save());
+    assert_eq!(vec![(3, 0), (3, 1)], sudoku.rotate90().cages()[0].cells);
+
+    for transformed in [solved.transpose(), solved.rotate90(), solved.mirror_horizontal()] {
+      assert!(transformed.is_solved());
+    }
+    for transformed in [sudoku.transpose(), sudoku.rotate90(), sudoku.mirror_horizontal()] {
+      assert!(crate::solver::has_unique_solution(&transformed, &lock));
+    }
+  }
+
+  #[test]
+  fn from_grid_matches_load() {
+    let data 
+      = "   3".to_string()
+      + "3  2"
+      + "2  1"
+      + "1   ";
+    let grid: [&[u8]; 4] = [
+      &[0, 0, 0, 3],
+      &[3, 0, 0, 2],
+      &[2, 0, 0, 1],
+      &[1, 0, 0, 0],
+    ];
+    assert_eq!(Ok(Sudoku::load(data.as_str(), 2)), Sudoku::from_grid(&grid, 2));
+  }
+
+  #[test]
+  fn from_grid_validation() {
+    let grid: [&[u8]; 4] = [&[0, 0, 0, 3], &[3, 0, 2], &[2, 0, 0, 1], &[1, 0, 0, 0]];
+    assert_eq!(
+      Err(LoadError::WrongRowLength { row: 1, expected: 4, found: 3 }),
+      Sudoku::from_grid(&grid, 2));
+    assert_eq!(Err(LoadError::WrongRowCount { expected: 4, found: 2 }), Sudoku::from_grid(&grid[..2], 2));
+
+    let grid: [&[u8]; 4] = [&[0, 0, 0, 3], &[3, 0, 0, 2], &[2, 0, 5, 1], &[1, 0, 0, 0]];
+    assert_eq!(Err(LoadError::DigitOutOfRange { pos: (2, 2), digit: 5 }), Sudoku::from_grid(&grid, 2));
+  }
+
+  #[test]
+  fn load_strict_clean_board() {
+    let data 
+      = "...3".to_string()
+      + "3002"
+      + "2  1"
+      + "1...";
+    let expected = Sudoku::load("   33  22  11   ", 2);
+    assert_eq!(Ok(expected), Sudoku::load_strict(data.as_str(), 2));
+  }
+
+  #[test]
+  fn load_strict_stray_letter() {
+    let data 
+      = "...3".to_string()
+      + "3x02"
+      + "2  1"
+      + "1...";
+    let expected = LoadError::InvalidCharacter { index: 5, character: 'x' };
+    assert_eq!(Err(expected), Sudoku::load_strict(data.as_str(), 2));
+    assert!(Sudoku::load_strict("...33..22..11..5", 2).is_err());
+    assert!(Sudoku::load_strict("...33..22..11..", 2).is_err());
+  }
+
+  #[test]
+  fn load_checked_clean_board() {
+    let data 
+      = "".to_string()
+      + "  8" + "  3" + "461"
+      + "2 6" + " 84" + "   "
+      + "3  " + "  7" + " 9 "
+
+      + " 3 " + "75 " + "68 "
+      + " 87" + " 1 " + "   "
+      + " 5 " + " 4 " + "13 "
+
+      + "  9" + "27 " + "31 "
+      + "   " + "   " + "   "
+      + "763" + "4 1" + "8 2";
+    assert_eq!(Ok(Sudoku::load(data.as_str(), 3)), Sudoku::load_checked(data.as_str(), 3));
+  }
+
+  #[test]
+  fn load_checked_duplicate_clue() {
+    let data 
+      = "".to_string()
+      + "9  " + "   " + "   "
+      + " 9 " + " 1 " + " 3 "
+      + "  6" + " 2 " + "7  "
+
+      + "   " + "3 4" + "   "
+      + "21 " + "   " + " 98"
+      + "   " + "   " + "   "
+
+      + "  2" + "5 6" + "4  "
+      + " 8 " + "   " + " 1 "
+      + "   " + "   " + "   ";
+    assert_eq!(Err(LoadError::Conflict(vec![((0, 0), (1, 1))])), Sudoku::load_checked(data.as_str(), 3));
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn json_round_trip() {
+    let data 
+      = "".to_string()
+      + "  8" + "  3" + "461"
+      + "2 6" + " 84" + "   "
+      + "3  " + "  7" + " 9 "
+
+      + " 3 " + "75 " + "68 "
+      + " 87" + " 1 " + "   "
+      + " 5 " + " 4 " + "13 "
+
+      + "  9" + "27 " + "31 "
+      + "   " + "   " + "   "
+      + "763" + "4 1" + "8 2";
+    let sudoku = Sudoku::load(data.as_str(), 3);
+
+    let json = serde_json::to_string(&sudoku).unwrap();
+    assert!(json.starts_with("{\"box_size\":3,\"cells\":[0,0,8,0,0,3,4,6,1,2,"));
+    assert_eq!(sudoku, serde_json::from_str(&json).unwrap());
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn json_wrong_cell_count() {
+    assert!(serde_json::from_str::<Sudoku>("{\"box_size\":2,\"cells\":[1,2,3]}").is_err());
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn json_outside_of_board() {
+    let cells = "0,".repeat(14);
+    let digit = format!("{{\"box_size\":2,\"cells\":[{}0,40]}}", cells);
+    let error = serde_json::from_str::<Sudoku>(&digit).unwrap_err().to_string();
+    assert!(error.contains(&LoadError::DigitOutOfRange { pos: (3, 3), digit: 40 }.to_string()));
+
+    let cage = format!("{{\"box_size\":2,\"cells\":[{}0,4],\"cages\":[{{\"cells\":[[0,0],[0,4]],\"sum\":5}}]}}", cells);
+    let error = serde_json::from_str::<Sudoku>(&cage).unwrap_err().to_string();
+    assert!(error.contains(&LoadError::CageCellOutside { cage: 0, pos: (0, 4) }.to_string()));
+
+    let valid = format!("{{\"box_size\":2,\"cells\":[{}0,4],\"cages\":[{{\"cells\":[[0,0],[0,3]],\"sum\":5}}]}}", cells);
+    assert!(serde_json::from_str::<Sudoku>(&valid).is_ok());
+  }
+
+  #[test]
+  fn load_checked_wrong_length() {
+    assert_eq!(Err(LoadError::WrongLength { expected: 16, found: 3 }), Sudoku::load_checked("12 ", 2));
+  }
 }
\ No newline at end of file
@@ -0,0 +1,1270 @@
+use crate::abort_lock::AbortLock;
+use crate::flags::Flags;
+use crate::matching::has_perfect_matching;
+use crate::sudoku::*;
+use itertools::Itertools;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Removal of a single candidate digit from a field
+pub type Elimination = (Position, u8);
+
+/// Digit which has to be written into a field
+pub type Placement = (Position, u8);
+
+/// Techniques known to the logical solver, ordered from the simplest
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum TechniqueKind {
+  NakedSingle,
+  HiddenSingle,
+  NakedPair,
+  HiddenPair,
+  PointingPair,
+  Claiming,
+  NakedTriple,
+  XWing,
+  Swordfish,
+  XYWing,
+  WWing,
+  SimpleColoring,
+}
+
+/// Techniques the logical solver is allowed to use, all of them by default
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct TechniqueSet {
+  mask: u32,
+}
+
+impl TechniqueSet {
+  pub fn all() -> Self {
+    TechniqueSet { mask: u32::MAX }
+  }
+
+  pub fn none() -> Self {
+    TechniqueSet { mask: 0 }
+  }
+
+  /// Naked and hidden singles only
+  pub fn singles() -> Self {
+    TechniqueSet::none().with(TechniqueKind::NakedSingle).with(TechniqueKind::HiddenSingle)
+  }
+
+  pub fn with(self, kind: TechniqueKind) -> Self {
+    TechniqueSet { mask: self.mask | 1 << kind as u32 }
+  }
+
+  pub fn without(self, kind: TechniqueKind) -> Self {
+    TechniqueSet { mask: self.mask & !(1 << kind as u32) }
+  }
+
+  pub fn contains(self, kind: TechniqueKind) -> bool {
+    self.mask & (1 << kind as u32) != 0
+  }
+}
+
+impl Default for TechniqueSet {
+  fn default() -> Self {
+    TechniqueSet::all()
+  }
+}
+
+/// Why a digit belongs into a field
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct HintExplanation {
+  /// Field and the digit forced into it
+  pub placement: Placement,
+  /// Technique which forced the digit, a naked or hidden single
+  pub technique: TechniqueKind,
+  /// Rounds of eliminations needed before the single showed up, in the order made
+  pub eliminations: Vec<(TechniqueKind, Vec<Elimination>)>,
+}
+
+/// Candidates of every field indexed as `[y][x]`, filled fields have none
+fn candidates(sudoku: &Sudoku) -> Vec<Vec<Flags>> {
+  (0 .. sudoku.board_size())
+    .map(|y| (0 .. sudoku.board_size())
+      .map(|x| match sudoku.at((x, y)) {
+        0 => sudoku.available((x, y)),
+        _ => Flags::empty()
+      })
+      .collect())
+    .collect()
+}
+
+fn boxes(sudoku: &Sudoku) -> Vec<Vec<Position>> {
+  sudoku.units().split_off(2 * sudoku.board_size())
+}
+
+fn naked_singles_in(cands: &[Vec<Flags>]) -> Vec<Placement> {
+  cands.iter().enumerate()
+    .flat_map(|(y, row)| row.iter().enumerate()
+      .filter_map(move |(x, flags)| flags.singleton().map(|digit| ((x, y), digit))))
+    .collect()
+}
+
+/// Digits which have only one possible field left in some unit
+fn hidden_singles_in(sudoku: &Sudoku, cands: &[Vec<Flags>]) -> Vec<Placement> {
+  let mut placements = vec![];
+  for unit in sudoku.units() {
+    for digit in 1 ..= sudoku.board_size() as u8 {
+      let holders: Vec<&Position> = unit.iter()
+        .filter(|(x, y)| cands[*y][*x].contains(digit))
+        .collect();
+      if holders.len() == 1 {
+        placements.push((*holders[0], digit));
+      }
+    }
+  }
+  placements.into_iter().unique().collect()
+}
+
+/// When `size` fields of a unit share exactly `size` candidates, those digits
+/// are removed from the rest of the unit
+fn naked_subsets_in(sudoku: &Sudoku, cands: &[Vec<Flags>], size: usize) -> Vec<Elimination> {
+  let mut eliminations = vec![];
+  for unit in sudoku.units() {
+    let small: Vec<&Position> = unit.iter()
+      .filter(|(x, y)| (2 ..= size).contains(&(cands[*y][*x].size() as usize)))
+      .collect();
+
+    for subset in small.into_iter().combinations(size) {
+      let digits = subset.iter()
+        .fold(Flags::empty(), |acc, (x, y)| acc.or(cands[*y][*x]));
+      if digits.size() as usize != size {
+        continue;
+      }
+
+      for (x, y) in unit.iter().filter(|pos| !subset.contains(pos)) {
+        for digit in digits.to_vec() {
+          if cands[*y][*x].contains(digit) {
+            eliminations.push(((*x, *y), digit));
+          }
+        }
+      }
+    }
+  }
+  eliminations.into_iter().unique().collect()
+}
+
+/// When `size` digits of a unit fit only into the same `size` fields, all other
+/// candidates are removed from those fields
+fn hidden_subsets_in(sudoku: &Sudoku, cands: &[Vec<Flags>], size: usize) -> Vec<Elimination> {
+  let mut eliminations = vec![];
+  for unit in sudoku.units() {
+    let holders = |digit: u8| unit.iter()
+      .filter(|(x, y)| cands[*y][*x].contains(digit))
+      .copied()
+      .collect::<Vec<Position>>();
+    let rare: Vec<u8> = (1 ..= sudoku.board_size() as u8)
+      .filter(|digit| (2 ..= size).contains(&holders(*digit).len()))
+      .collect();
+
+    for digits in rare.into_iter().combinations(size) {
+      let fields: Vec<Position> = digits.iter()
+        .flat_map(|digit| holders(*digit))
+        .unique()
+        .collect();
+      if fields.len() != size {
+        continue;
+      }
+
+      let kept = Flags::from_vec(digits);
+      for (x, y) in fields {
+        for digit in cands[y][x].to_vec() {
+          if !kept.contains(digit) {
+            eliminations.push(((x, y), digit));
+          }
+        }
+      }
+    }
+  }
+  eliminations.into_iter().unique().collect()
+}
+
+/// When all candidates for a digit inside a box lie on one line, the digit is
+/// removed from the rest of that line
+fn pointing_in(sudoku: &Sudoku, cands: &[Vec<Flags>]) -> Vec<Elimination> {
+  let board_size = sudoku.board_size();
+  let mut eliminations = vec![];
+  for unit in boxes(sudoku) {
+    for digit in 1 ..= board_size as u8 {
+      let holders: Vec<&Position> = unit.iter()
+        .filter(|(x, y)| cands[*y][*x].contains(digit))
+        .collect();
+      if holders.len() < 2 {
+        continue;
+      }
+
+      let line: Vec<Position> = if holders.iter().map(|(_, y)| y).all_equal() {
+        (0 .. board_size).map(|x| (x, holders[0].1)).collect()
+      } else if holders.iter().map(|(x, _)| x).all_equal() {
+        (0 .. board_size).map(|y| (holders[0].0, y)).collect()
+      } else {
+        continue;
+      };
+
+      for (x, y) in line.into_iter().filter(|pos| !unit.contains(pos)) {
+        if cands[y][x].contains(digit) {
+          eliminations.push(((x, y), digit));
+        }
+      }
+    }
+  }
+  eliminations.into_iter().unique().collect()
+}
+
+/// When all candidates for a digit in a row or column lie in one box, the digit is
+/// removed from the rest of that box
+fn claiming_in(sudoku: &Sudoku, cands: &[Vec<Flags>]) -> Vec<Elimination> {
+  let lines = sudoku.units().into_iter().take(2 * sudoku.board_size());
+  let mut eliminations = vec![];
+  for line in lines {
+    for digit in 1 ..= sudoku.board_size() as u8 {
+      let holders: Vec<&Position> = line.iter()
+        .filter(|(x, y)| cands[*y][*x].contains(digit))
+        .collect();
+      if holders.len() < 2 || !holders.iter().map(|pos| sudoku.box_index(**pos)).all_equal() {
+        continue;
+      }
+
+      let index = sudoku.box_index(*holders[0]);
+      let box_pos = (index % sudoku.box_size(), index / sudoku.box_size());
+      for (x, y) in sudoku.cells_in_box(box_pos).into_iter().filter(|pos| !line.contains(pos)) {
+        if cands[y][x].contains(digit) {
+          eliminations.push(((x, y), digit));
+        }
+      }
+    }
+  }
+  eliminations.into_iter().unique().collect()
+}
+
+/// Finds basic fish of given size: when `size` rows hold all their candidates for
+/// a digit within `size` columns, the digit is removed from the rest of those columns
+/// (and the same with rows and columns swapped)
+fn fish(cands: &[Vec<Flags>], size: usize) -> Vec<Elimination> {
+  let board_size = cands.len();
+  let mut eliminations = vec![];
+
+  for digit in 1 ..= board_size as u8 {
+    for by_columns in [false, true] {
+      let at = |line: usize, cross: usize| if by_columns { (line, cross) } else { (cross, line) };
+      let holds = |(x, y): Position| cands[y][x].contains(digit);
+
+      let base_lines: Vec<(usize, Vec<usize>)> = (0 .. board_size)
+        .map(|line| (line, (0 .. board_size).filter(|cross| holds(at(line, *cross))).collect::<Vec<_>>()))
+        .filter(|(_, crosses)| (2 ..= size).contains(&crosses.len()))
+        .collect();
+
+      for base in base_lines.iter().combinations(size) {
+        let covered: Vec<usize> = base.iter()
+          .flat_map(|(_, crosses)| crosses.iter().copied())
+          .sorted()
+          .dedup()
+          .collect();
+        if covered.len() != size {
+          continue;
+        }
+
+        for cross in covered {
+          for line in 0 .. board_size {
+            let pos = at(line, cross);
+            if holds(pos) && base.iter().all(|(l, _)| *l != line) {
+              eliminations.push((pos, digit));
+            }
+          }
+        }
+      }
+    }
+  }
+  eliminations.into_iter().unique().collect()
+}
+
+/// Pivot with candidates {X, Y} seeing pincers with {X, Z} and {Y, Z}: one of the
+/// pincers has to be Z, so Z is removed from fields seeing both of them
+fn xy_wing_in(sudoku: &Sudoku, cands: &[Vec<Flags>]) -> Vec<Elimination> {
+  let all_positions: Vec<Position> = (0 .. sudoku.board_size())
+    .flat_map(|y| (0 .. sudoku.board_size()).map(move |x| (x, y)))
+    .collect();
+  let bivalue: Vec<(Position, Flags)> = all_positions.iter()
+    .map(|(x, y)| ((*x, *y), cands[*y][*x]))
+    .filter(|(_, flags)| flags.size() == 2)
+    .collect();
+
+  let mut eliminations = vec![];
+  for (pivot, pivot_flags) in bivalue.iter() {
+    for (first, second) in pivot_flags.to_vec().into_iter().permutations(2).map(|p| (p[0], p[1])) {
+      let pincers = |digit: u8| bivalue.iter()
+        .filter(move |(pos, flags)| sudoku.sees(*pivot, *pos) && flags.contains(digit) && *flags != *pivot_flags);
+
+      for (a, a_flags) in pincers(first) {
+        let z = a_flags.unset(first).singleton().unwrap();
+        let wanted = Flags::empty().set(second).set(z);
+        for (b, _) in pincers(second).filter(|(_, flags)| *flags == wanted) {
+          for (x, y) in all_positions.iter() {
+            if cands[*y][*x].contains(z) && sudoku.sees(*a, (*x, *y)) && sudoku.sees(*b, (*x, *y)) {
+              eliminations.push(((*x, *y), z));
+            }
+          }
+        }
+      }
+    }
+  }
+  eliminations.into_iter().unique().collect()
+}
+
+/// Digits fitting into exactly two fields of some unit, along with the two fields.
+/// One of them has to hold the digit, so the pair is a strong link.
+fn strong_links(sudoku: &Sudoku, cands: &[Vec<Flags>]) -> Vec<(u8, Position, Position)> {
+  sudoku.units().into_iter()
+    .flat_map(|unit| (1 ..= sudoku.board_size() as u8).filter_map(move |digit| {
+      let fields: Vec<Position> = unit.iter()
+        .filter(|(x, y)| cands[*y][*x].contains(digit))
+        .copied()
+        .collect();
+      match fields[..] {
+        [first, second] => Some((digit, first, second)),
+        _ => None
+      }
+    }))
+    .unique()
+    .collect()
+}
+
+/// Two fields with the same candidates {X, Y} not seeing each other, connected by
+/// a strong link on Y: a unit where Y fits only into two fields, one seen by each of
+/// them. Both can't be Y as the link needs one Y, so one of them is X and X is
+/// removed from fields seeing both
+fn w_wing_in(sudoku: &Sudoku, cands: &[Vec<Flags>]) -> Vec<Elimination> {
+  let all_positions: Vec<Position> = (0 .. sudoku.board_size())
+    .flat_map(|y| (0 .. sudoku.board_size()).map(move |x| (x, y)))
+    .collect();
+  let bivalue: Vec<(Position, Flags)> = all_positions.iter()
+    .map(|(x, y)| ((*x, *y), cands[*y][*x]))
+    .filter(|(_, flags)| flags.size() == 2)
+    .collect();
+  let strong_links = strong_links(sudoku, cands);
+
+  let mut eliminations = vec![];
+  for ((a, a_flags), (b, _)) in bivalue.iter().tuple_combinations()
+      .filter(|((a, a_flags), (b, b_flags))| a_flags == b_flags && !sudoku.sees(*a, *b)) {
+    for (linked, removed) in a_flags.to_vec().into_iter().permutations(2).map(|p| (p[0], p[1])) {
+      let connected = strong_links.iter()
+        .filter(|(digit, _, _)| *digit == linked)
+        .flat_map(|(_, first, second)| vec![(*first, *second), (*second, *first)])
+        .any(|(p, q)| ![p, q].contains(a) && ![p, q].contains(b) && sudoku.sees(*a, p) && sudoku.sees(*b, q));
+      if !connected {
+        continue;
+      }
+      for (x, y) in all_positions.iter() {
+        if cands[*y][*x].contains(removed) && sudoku.sees(*a, (*x, *y)) && sudoku.sees(*b, (*x, *y)) {
+          eliminations.push(((*x, *y), removed));
+        }
+      }
+    }
+  }
+  eliminations.into_iter().unique().collect()
+}
+
+/// Field forced by cross-hatching: a digit missing from a unit fits into only one
+/// of its fields once rows, columns and boxes already holding it are crossed out.
+/// Boxes are scanned first, then rows and columns. The text describes the step the
+/// way a beginner would do it, with rows, columns and boxes numbered from 1.
+pub fn scanning_hint(sudoku: &Sudoku) -> Option<(Position, u8, String)> {
+  let cands = candidates(sudoku);
+  let n = sudoku.board_size();
+  let mut units: Vec<(usize, Vec<Position>)> = sudoku.units().into_iter().enumerate().collect();
+  units.rotate_left(2 * n);
+
+  units.into_iter().find_map(|(index, unit)| {
+    (1 ..= n as u8).find_map(|digit| {
+      let fits: Vec<Position> = unit.iter()
+        .filter(|(x, y)| cands[*y][*x].contains(digit))
+        .copied()
+        .collect();
+      let (x, y) = match fits[..] {
+        [pos] => pos,
+        _ => return None
+      };
+      let text = match index / n {
+        0 => format!("In row {}, {} fits only into column {}", y + 1, digit, x + 1),
+        1 => format!("In column {}, {} fits only into row {}", x + 1, digit, y + 1),
+        _ => format!("In box {}, {} fits only into row {}, column {}",
+          sudoku.box_index((x, y)) + 1, digit, y + 1, x + 1),
+      };
+      Some(((x, y), digit, text))
+    })
+  })
+}
+
+/// Strong links of one digit chained together and colored alternately, exactly one
+/// of the colors holds the digit in each chain. When two fields of the same color see
+/// each other that color is false and the digit goes from all its fields. Otherwise
+/// the digit is removed from fields seeing both colors of a chain.
+fn simple_coloring_in(sudoku: &Sudoku, cands: &[Vec<Flags>]) -> Vec<Elimination> {
+  let links = strong_links(sudoku, cands);
+  let mut eliminations = vec![];
+
+  for digit in 1 ..= sudoku.board_size() as u8 {
+    let edges: Vec<(Position, Position)> = links.iter()
+      .filter(|(linked, _, _)| *linked == digit)
+      .map(|(_, first, second)| (*first, *second))
+      .collect();
+    let mut colors: HashMap<Position, (usize, bool)> = HashMap::new();
+    let mut chains = 0;
+    for (start, _) in edges.iter() {
+      if colors.contains_key(start) {
+        continue;
+      }
+      colors.insert(*start, (chains, true));
+      let mut pending = vec![*start];
+      while let Some(pos) = pending.pop() {
+        let (_, color) = colors[&pos];
+        for (first, second) in edges.iter() {
+          let next = match (*first == pos, *second == pos) {
+            (true, _) => *second,
+            (_, true) => *first,
+            _ => continue
+          };
+          if let Entry::Vacant(entry) = colors.entry(next) {
+            entry.insert((chains, !color));
+            pending.push(next);
+          }
+        }
+      }
+      chains += 1;
+    }
+
+    for chain in 0 .. chains {
+      let fields = |wanted: bool| colors.iter()
+        .filter(move |(_, (c, color))| *c == chain && *color == wanted)
+        .map(|(pos, _)| *pos)
+        .sorted();
+      let wrapped = [true, false].iter().copied()
+        .find(|color| fields(*color).tuple_combinations().any(|(a, b)| sudoku.sees(a, b)));
+      match wrapped {
+        Some(color) => eliminations.extend(fields(color).map(|pos| (pos, digit))),
+        None => {
+          for (x, y) in (0 .. sudoku.board_size()).flat_map(|y| (0 .. sudoku.board_size()).map(move |x| (x, y))) {
+            let sees_color = |wanted: bool| fields(wanted).any(|pos| sudoku.sees(pos, (x, y)));
+            if cands[y][x].contains(digit) && !colors.contains_key(&(x, y)) && sees_color(true) && sees_color(false) {
+              eliminations.push(((x, y), digit));
+            }
+          }
+        }
+      }
+    }
+  }
+  eliminations.into_iter().unique().collect()
+}
+
+pub fn naked_singles(sudoku: &Sudoku) -> Vec<Placement> {
+  naked_singles_in(&candidates(sudoku))
+}
+
+pub fn hidden_singles(sudoku: &Sudoku) -> Vec<Placement> {
+  hidden_singles_in(sudoku, &candidates(sudoku))
+}
+
+pub fn naked_pairs(sudoku: &Sudoku) -> Vec<Elimination> {
+  naked_subsets_in(sudoku, &candidates(sudoku), 2)
+}
+
+pub fn naked_triples(sudoku: &Sudoku) -> Vec<Elimination> {
+  naked_subsets_in(sudoku, &candidates(sudoku), 3)
+}
+
+pub fn hidden_pairs(sudoku: &Sudoku) -> Vec<Elimination> {
+  hidden_subsets_in(sudoku, &candidates(sudoku), 2)
+}
+
+pub fn pointing_pairs(sudoku: &Sudoku) -> Vec<Elimination> {
+  pointing_in(sudoku, &candidates(sudoku))
+}
+
+pub fn claiming(sudoku: &Sudoku) -> Vec<Elimination> {
+  claiming_in(sudoku, &candidates(sudoku))
+}
+
+pub fn x_wing(sudoku: &Sudoku) -> Vec<Elimination> {
+  fish(&candidates(sudoku), 2)
+}
+
+pub fn swordfish(sudoku: &Sudoku) -> Vec<Elimination> {
+  fish(&candidates(sudoku), 3)
+}
+
+pub fn xy_wing(sudoku: &Sudoku) -> Vec<Elimination> {
+  xy_wing_in(sudoku, &candidates(sudoku))
+}
+
+pub fn w_wing(sudoku: &Sudoku) -> Vec<Elimination> {
+  w_wing_in(sudoku, &candidates(sudoku))
+}
+
+pub fn simple_coloring(sudoku: &Sudoku) -> Vec<Elimination> {
+  simple_coloring_in(sudoku, &candidates(sudoku))
+}
+
+/// Board with every digit `propagate` finds forced, without guessing. Every digit is
+/// checked against the candidates left right before it is written, so a broken board
+/// never gets conflicting digits from singles found earlier.
+pub fn fill_obvious(sudoku: Sudoku) -> Sudoku {
+  propagate(sudoku).0
+}
+
+/// Candidates which no assignment of distinct digits to the empty fields of some unit
+/// can use. Once they are gone the all different constraint of every unit is arc
+/// consistent, which covers naked and hidden subsets of any size.
+fn matching_eliminations(sudoku: &Sudoku, cands: &[Vec<Flags>]) -> Vec<Elimination> {
+  let mut eliminations = vec![];
+  for unit in sudoku.units() {
+    let options: Vec<Flags> = unit.iter()
+      .map(|(x, y)| match sudoku.at((*x, *y)) {
+        0 => cands[*y][*x],
+        digit => Flags::empty().set(digit)
+      })
+      .collect();
+    for (i, (x, y)) in unit.iter().enumerate().filter(|(_, pos)| sudoku.at(**pos) == 0) {
+      for digit in cands[*y][*x].iter() {
+        let mut restricted = options.clone();
+        restricted[i] = Flags::empty().set(digit);
+        if !has_perfect_matching(restricted) {
+          eliminations.push(((*x, *y), digit));
+        }
+      }
+    }
+  }
+  eliminations.into_iter().unique().collect()
+}
+
+/// Constraint propagation without any guessing: every forced field gets its digit,
+/// which is then removed from candidates of its peers, every digit with a single
+/// place left in a unit is placed, and candidates no assignment of distinct digits
+/// to a unit can use are eliminated, until nothing changes. Returns the reduced board
+/// along with candidates left for a search, an empty field without any candidate
+/// means the board has no solution.
+pub fn propagate(sudoku: Sudoku) -> (Sudoku, CandidateBoard) {
+  let mut board = CandidateBoard::new(sudoku);
+  loop {
+    let mut singles = board.forced();
+    singles.extend(hidden_singles_in(&board.sudoku, &board.cands));
+    let mut placed = false;
+    for (pos, digit) in singles {
+      if board.candidates(pos).contains(digit) {
+        board.place(pos, digit);
+        placed = true;
+      }
+    }
+    if placed {
+      continue;
+    }
+    let eliminations = matching_eliminations(&board.sudoku, &board.cands);
+    if board.apply_eliminations(&eliminations) == 0 {
+      return (board.sudoku.clone(), board);
+    }
+  }
+}
+
+/// Board along with the candidates still left for each of its empty fields. Unlike
+/// `Sudoku::available` it keeps eliminations made by techniques, so they add up
+/// before any field gets forced.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CandidateBoard {
+  sudoku: Sudoku,
+  cands: Vec<Vec<Flags>>,
+}
+
+impl CandidateBoard {
+  pub fn new(sudoku: Sudoku) -> Self {
+    let cands = candidates(&sudoku);
+    CandidateBoard { sudoku, cands }
+  }
+
+  pub fn sudoku(&self) -> &Sudoku {
+    &self.sudoku
+  }
+
+  pub fn into_sudoku(self) -> Sudoku {
+    self.sudoku
+  }
+
+  /// Candidates left for the field, none for a filled one
+  pub fn candidates(&self, (x, y): Position) -> Flags {
+    self.cands[y][x]
+  }
+
+  /// Removes digit from candidates of the field, returns whether it was still there
+  pub fn eliminate(&mut self, (x, y): Position, digit: u8) -> bool {
+    let present = self.cands[y][x].contains(digit);
+    self.cands[y][x] = self.cands[y][x].unset(digit);
+    present
+  }
+
+  /// Applies eliminations found by a technique, returns how many of them removed something
+  pub fn apply_eliminations(&mut self, eliminations: &[Elimination]) -> usize {
+    eliminations.iter()
+      .filter(|(pos, digit)| self.eliminate(*pos, *digit))
+      .count()
+  }
+
+  /// Writes digit into the field and removes it from candidates of all fields sharing a unit
+  pub fn place(&mut self, pos: Position, digit: u8) {
+    for (x, y) in self.sudoku.peers(pos) {
+      self.cands[y][x] = self.cands[y][x].unset(digit);
+    }
+    self.cands[pos.1][pos.0] = Flags::empty();
+    self.sudoku = self.sudoku.clone().set(pos, digit);
+  }
+
+  /// Empty fields left with a single candidate
+  pub fn forced(&self) -> Vec<Placement> {
+    naked_singles_in(&self.cands)
+  }
+}
+
+impl From<Sudoku> for CandidateBoard {
+  fn from(sudoku: Sudoku) -> Self {
+    CandidateBoard::new(sudoku)
+  }
+}
+
+/// Elimination techniques in the order the logical solver tries them
+const ELIMINATION_ORDER: [TechniqueKind; 10] = [
+  TechniqueKind::NakedPair,
+  TechniqueKind::HiddenPair,
+  TechniqueKind::PointingPair,
+  TechniqueKind::Claiming,
+  TechniqueKind::NakedTriple,
+  TechniqueKind::XWing,
+  TechniqueKind::Swordfish,
+  TechniqueKind::XYWing,
+  TechniqueKind::WWing,
+  TechniqueKind::SimpleColoring,
+];
+
+fn eliminations_by(kind: TechniqueKind, sudoku: &Sudoku, cands: &[Vec<Flags>]) -> Vec<Elimination> {
+  match kind {
+    TechniqueKind::NakedSingle | TechniqueKind::HiddenSingle => vec![],
+    TechniqueKind::NakedPair => naked_subsets_in(sudoku, cands, 2),
+    TechniqueKind::HiddenPair => hidden_subsets_in(sudoku, cands, 2),
+    TechniqueKind::PointingPair => pointing_in(sudoku, cands),
+    TechniqueKind::Claiming => claiming_in(sudoku, cands),
+    TechniqueKind::NakedTriple => naked_subsets_in(sudoku, cands, 3),
+    TechniqueKind::XWing => fish(cands, 2),
+    TechniqueKind::Swordfish => fish(cands, 3),
+    TechniqueKind::XYWing => xy_wing_in(sudoku, cands),
+    TechniqueKind::WWing => w_wing_in(sudoku, cands),
+    TechniqueKind::SimpleColoring => simple_coloring_in(sudoku, cands),
+  }
+}
+
+/// Hash of candidates left in every field, same for boards where techniques made
+/// no progress. Placing a digit empties candidates of its field, so it counts too.
+pub fn progress_fingerprint(board: &CandidateBoard) -> u64 {
+  let mut hasher = DefaultHasher::new();
+  board.cands.hash(&mut hasher);
+  hasher.finish()
+}
+
+/// Logical solving loop, every placed single and every productive round of an
+/// elimination technique is reported to `record`. Stops once a whole iteration
+/// leaves the fingerprint of the candidates unchanged.
+fn solve_logically_traced(sudoku: Sudoku, techniques: TechniqueSet, lock: &AbortLock, 
+    mut record: impl FnMut(TechniqueKind)) -> (Sudoku, bool) {
+  let mut board = CandidateBoard::new(sudoku);
+
+  while !lock.is_aborted() {
+    let fingerprint = progress_fingerprint(&board);
+    let placements = vec![
+      (TechniqueKind::NakedSingle, board.forced()),
+      (TechniqueKind::HiddenSingle, hidden_singles_in(&board.sudoku, &board.cands)),
+    ];
+    let mut changes = 0;
+    for (kind, found) in placements.into_iter().filter(|(kind, _)| techniques.contains(*kind)) {
+      for (pos, digit) in found {
+        if board.candidates(pos).contains(digit) {
+          board.place(pos, digit);
+          record(kind);
+          changes += 1;
+        }
+      }
+    }
+    if changes > 0 {
+      continue;
+    }
+
+    for kind in ELIMINATION_ORDER.iter().filter(|kind| techniques.contains(**kind)) {
+      changes += board.apply_eliminations(&eliminations_by(*kind, &board.sudoku, &board.cands));
+      if changes > 0 {
+        record(*kind);
+        break;
+      }
+    }
+    if progress_fingerprint(&board) == fingerprint {
+      break;
+    }
+  }
+
+  let sudoku = board.into_sudoku();
+  let solved = sudoku.is_solved();
+  (sudoku, solved)
+}
+
+/// Makes the next move of the logical solver: places the easiest single, or when
+/// there is none, eliminates candidates with the simplest productive technique until
+/// one shows up. A board only holds digits, so eliminations on their own would not
+/// change it and are reported in the explanation of the placement they lead to.
+/// `None` when only guessing could make progress.
+pub fn step(sudoku: Sudoku, lock: &AbortLock) -> Option<(Sudoku, HintExplanation)> {
+  let mut board = CandidateBoard::new(sudoku);
+  let mut eliminations = vec![];
+
+  while !lock.is_aborted() {
+    let single = board.forced().into_iter().next()
+      .map(|placement| (TechniqueKind::NakedSingle, placement))
+      .or_else(|| hidden_singles_in(&board.sudoku, &board.cands).into_iter().next()
+        .map(|placement| (TechniqueKind::HiddenSingle, placement)));
+    if let Some((technique, (pos, digit))) = single {
+      board.place(pos, digit);
+      let explanation = HintExplanation { placement: (pos, digit), technique, eliminations };
+      return Some((board.into_sudoku(), explanation));
+    }
+
+    let round = elimination_round(&board)?;
+    board.apply_eliminations(&round.1);
+    eliminations.push(round);
+  }
+  None
+}
+
+/// Eliminations of the simplest technique still removing some candidate
+fn elimination_round(board: &CandidateBoard) -> Option<(TechniqueKind, Vec<Elimination>)> {
+  ELIMINATION_ORDER.iter()
+    .map(|kind| {
+      let found: Vec<Elimination> = eliminations_by(*kind, &board.sudoku, &board.cands).into_iter()
+        .filter(|(pos, digit)| board.candidates(*pos).contains(*digit))
+        .collect();
+      (*kind, found)
+    })
+    .find(|(_, found)| !found.is_empty())
+}
+
+/// Explains why the given empty field has to hold its digit. Only eliminations are
+/// made on the way, no other field gets filled, so `None` means the field is not
+/// determined by the current board yet (or is not empty).
+pub fn explain_cell(sudoku: &Sudoku, pos: Position, lock: &AbortLock) -> Option<HintExplanation> {
+  if sudoku.at(pos) != 0 {
+    return None;
+  }
+  let mut board = CandidateBoard::new(sudoku.clone());
+  let mut eliminations = vec![];
+
+  while !lock.is_aborted() {
+    let single = board.candidates(pos).singleton()
+      .map(|digit| (TechniqueKind::NakedSingle, digit))
+      .or_else(|| hidden_singles_in(&board.sudoku, &board.cands).into_iter()
+        .find(|(field, _)| *field == pos)
+        .map(|(_, digit)| (TechniqueKind::HiddenSingle, digit)));
+    if let Some((technique, digit)) = single {
+      return Some(HintExplanation { placement: (pos, digit), technique, eliminations });
+    }
+
+    let round = elimination_round(&board)?;
+    board.apply_eliminations(&round.1);
+    eliminations.push(round);
+  }
+  None
+}
+
+/// Repeatedly applies the simplest technique making progress, without ever guessing.
+/// Returns the reduced board and whether it got fully solved.
+pub fn solve_logically(sudoku: Sudoku, lock: &AbortLock) -> (Sudoku, bool) {
+  solve_logically_using(sudoku, TechniqueSet::all(), lock)
+}
+
+/// Same as `solve_logically`, restricted to the given techniques
+pub fn solve_logically_using(sudoku: Sudoku, techniques: TechniqueSet, lock: &AbortLock) -> (Sudoku, bool) {
+  solve_logically_traced(sudoku, techniques, lock, |_| {})
+}
+
+/// Counts how many times each technique was used while solving logically, along
+/// with whether logic alone was enough. Every placed single counts once, elimination
+/// techniques count once per productive round.
+pub fn difficulty_histogram(sudoku: Sudoku, lock: &AbortLock) -> (HashMap<TechniqueKind, usize>, bool) {
+  let mut histogram = HashMap::new();
+  let (_, solved) = solve_logically_traced(sudoku, TechniqueSet::all(), lock, |kind| {
+    *histogram.entry(kind).or_insert(0) += 1;
+  });
+  (histogram, solved)
+}
+
+/// Hardest technique the logical solver needed, along with whether it got the
+/// board solved. `None` when no technique could be applied at all.
+pub fn rate_difficulty(sudoku: Sudoku, lock: &AbortLock) -> (Option<TechniqueKind>, bool) {
+  rate_difficulty_using(sudoku, TechniqueSet::all(), lock)
+}
+
+/// Same as `rate_difficulty`, restricted to the given techniques
+pub fn rate_difficulty_using(sudoku: Sudoku, techniques: TechniqueSet, lock: &AbortLock) 
+    -> (Option<TechniqueKind>, bool) {
+  let mut hardest = None;
+  let (_, solved) = solve_logically_traced(sudoku, techniques, lock, |kind| {
+    hardest = hardest.max(Some(kind));
+  });
+  (hardest, solved)
+}
+
+#[cfg(test)]
+mod test {
+  use crate::techniques::*;
+
+  #[test]
+  fn medium_sudoku_solved_logically() {
+    let data 
+      = "".to_string()
+      + "  8" + "  3" + "461"
+      + "2 6" + " 84" + "   "
+      + "3  " + "  7" + " 9 "
+
+      + " 3 " + "75 " + "68 "
+      + " 87" + " 1 " + "   "
+      + " 5 " + " 4 " + "13 "
+
+      + "  9" + "27 " + "31 "
+      + "   " + "   " + "   "
+      + "763" + "4 1" + "8 2";
+
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    let (solved, is_solved) = solve_logically(sudoku, &AbortLock::prepare());
+    assert!(is_solved);
+    assert!(solved.is_solved());
+  }
+
+  #[test]
+  fn eliminations_force_placement() {
+    let mut board = CandidateBoard::from(Sudoku::empty(2));
+    assert_eq!(1, board.apply_eliminations(&[((0, 0), 1), ((0, 0), 1)]));
+    assert!(board.forced().is_empty());
+    assert!(board.eliminate((0, 0), 2));
+    assert!(!board.eliminate((0, 0), 2));
+    assert!(board.eliminate((0, 0), 3));
+    assert_eq!(vec![((0, 0), 4)], board.forced());
+
+    board.place((0, 0), 4);
+    assert_eq!(4, board.sudoku().at((0, 0)));
+    assert_eq!(Flags::empty(), board.candidates((0, 0)));
+    assert!(!board.candidates((1, 1)).contains(4));
+    assert!(board.forced().is_empty());
+  }
+
+  #[test]
+  fn obvious_fields_filled() {
+    let data 
+      = "".to_string()
+      + "  8" + "  3" + "461"
+      + "2 6" + " 84" + "   "
+      + "3  " + "  7" + " 9 "
+
+      + " 3 " + "75 " + "68 "
+      + " 87" + " 1 " + "   "
+      + " 5 " + " 4 " + "13 "
+
+      + "  9" + "27 " + "31 "
+      + "   " + "   " + "   "
+      + "763" + "4 1" + "8 2";
+
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    let filled = fill_obvious(sudoku.clone());
+    assert!(filled.remaining_cells() + 20 <= sudoku.remaining_cells());
+    assert!(filled.conflicts().is_empty());
+    assert!(sudoku.iter().all(|(digit, pos)| digit == 0 || filled.at(pos) == digit));
+    assert_eq!(Sudoku::empty(3), fill_obvious(Sudoku::empty(3)));
+  }
+
+  #[test]
+  fn propagation_without_search() {
+    let trivial 
+      = "   3".to_string()
+      + "3  2"
+      + "2  1"
+      + "1   ";
+    let (solved, board) = propagate(Sudoku::load(trivial.as_str(), 2));
+    assert!(solved.is_solved());
+    assert_eq!(&solved, board.sudoku());
+
+    let medium 
+      = "".to_string()
+      + "  8" + "  3" + "461"
+      + "2 6" + " 84" + "   "
+      + "3  " + "  7" + " 9 "
+
+      + " 3 " + "75 " + "68 "
+      + " 87" + " 1 " + "   "
+      + " 5 " + " 4 " + "13 "
+
+      + "  9" + "27 " + "31 "
+      + "   " + "   " + "   "
+      + "763" + "4 1" + "8 2";
+    let sudoku = Sudoku::load(medium.as_str(), 3);
+    let candidate_count = |board: &CandidateBoard| sudoku.iter()
+      .map(|(_, pos)| board.candidates(pos).size() as usize)
+      .sum::<usize>();
+    let (reduced, board) = propagate(sudoku.clone());
+    assert!(4 * candidate_count(&board) <= candidate_count(&CandidateBoard::new(sudoku.clone())));
+    assert!(reduced.conflicts().is_empty());
+    assert!(sudoku.iter().all(|(digit, pos)| digit == 0 || reduced.at(pos) == digit));
+
+    let beyond_singles 
+      = "".to_string()
+      + "4  " + "   " + "8 5"
+      + " 3 " + "   " + "   "
+      + "   " + "7  " + "   "
+
+      + " 2 " + "   " + " 6 "
+      + "   " + " 8 " + "4  "
+      + "   " + " 1 " + "   "
+
+      + "   " + "6 3" + " 7 "
+      + "5  " + "2  " + "   "
+      + "1 4" + "   " + "   ";
+    let sudoku = Sudoku::load(beyond_singles.as_str(), 3);
+    assert!(!solve_logically_using(sudoku.clone(), TechniqueSet::singles(), &AbortLock::prepare()).1);
+    assert!(fill_obvious(sudoku).is_solved());
+  }
+
+  #[test]
+  fn cross_hatching_hint() {
+    let sudoku = Sudoku::empty(3)
+      .set((4, 0), 5)
+      .set((7, 1), 5)
+      .set((1, 4), 5)
+      .set((2, 7), 5);
+    let expected = ((0, 2), 5, "In box 1, 5 fits only into row 3, column 1".to_string());
+    assert_eq!(Some(expected), scanning_hint(&sudoku));
+
+    let cleared = Sudoku::load("12  341 2 43    ", 2).set((0, 0), 0);
+    assert_eq!(Some(((0, 0), 1, "In box 1, 1 fits only into row 1, column 1".to_string())), scanning_hint(&cleared));
+    assert_eq!(None, scanning_hint(&Sudoku::empty(3)));
+  }
+
+  #[test]
+  fn explain_clicked_cell() {
+    let data 
+      = "".to_string()
+      + "  8" + "  3" + "461"
+      + "2 6" + " 84" + "   "
+      + "3  " + "  7" + " 9 "
+
+      + " 3 " + "75 " + "68 "
+      + " 87" + " 1 " + "   "
+      + " 5 " + " 4 " + "13 "
+
+      + "  9" + "27 " + "31 "
+      + "   " + "   " + "   "
+      + "763" + "4 1" + "8 2";
+
+    let lock = AbortLock::prepare();
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    let explanation = explain_cell(&sudoku, (8, 1), &lock).unwrap();
+    assert_eq!(((8, 1), 3), explanation.placement);
+    assert_eq!(TechniqueKind::HiddenSingle, explanation.technique);
+    assert!(explanation.eliminations.is_empty());
+
+    assert_eq!(TechniqueKind::NakedSingle, explain_cell(&sudoku, (2, 5), &lock).unwrap().technique);
+    assert!(explain_cell(&sudoku, (2, 0), &lock).is_none());
+    assert!(explain_cell(&Sudoku::empty(3), (0, 0), &lock).is_none());
+  }
+
+  #[test]
+  fn stepping_solves_medium_sudoku() {
+    let data 
+      = "".to_string()
+      + "  8" + "  3" + "461"
+      + "2 6" + " 84" + "   "
+      + "3  " + "  7" + " 9 "
+
+      + " 3 " + "75 " + "68 "
+      + " 87" + " 1 " + "   "
+      + " 5 " + " 4 " + "13 "
+
+      + "  9" + "27 " + "31 "
+      + "   " + "   " + "   "
+      + "763" + "4 1" + "8 2";
+
+    let lock = AbortLock::prepare();
+    let mut sudoku = Sudoku::load(data.as_str(), 3);
+    let empty_fields = sudoku.remaining_cells();
+    let mut steps = 0;
+    while let Some((next, explanation)) = step(sudoku.clone(), &lock) {
+      let ((x, y), digit) = explanation.placement;
+      assert_eq!(0, sudoku.at((x, y)));
+      assert_eq!(digit, next.at((x, y)));
+      assert!(explanation.technique <= TechniqueKind::HiddenSingle);
+      sudoku = next;
+      steps += 1;
+    }
+    assert!(sudoku.is_solved());
+    assert_eq!(empty_fields, steps);
+  }
+
+  #[test]
+  fn medium_sudoku_histogram() {
+    let data 
+      = "".to_string()
+      + "  8" + "  3" + "461"
+      + "2 6" + " 84" + "   "
+      + "3  " + "  7" + " 9 "
+
+      + " 3 " + "75 " + "68 "
+      + " 87" + " 1 " + "   "
+      + " 5 " + " 4 " + "13 "
+
+      + "  9" + "27 " + "31 "
+      + "   " + "   " + "   "
+      + "763" + "4 1" + "8 2";
+
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    let empty_fields = sudoku.remaining_cells();
+    let (histogram, solved) = difficulty_histogram(sudoku, &AbortLock::prepare());
+    assert!(solved);
+    assert!(histogram.keys().all(|kind| *kind <= TechniqueKind::HiddenSingle));
+    assert_eq!(empty_fields, histogram.values().sum::<usize>());
+  }
+
+  #[test]
+  fn hardest_for_logic_stays_partial() {
+    let data 
+      = "".to_string()
+      + "8  " + "   " + "   "
+      + "  3" + "6  " + "   "
+      + " 7 " + "  9" + "2  "
+
+      + " 5 " + "  7" + "   "
+      + "   " + " 45" + "7  "
+      + "   " + "1  " + " 3 "
+
+      + "  1" + "   " + " 68"
+      + "  8" + "5  " + " 1 "
+      + " 9 " + "   " + "4  ";
+
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    let (reduced, is_solved) = solve_logically(sudoku.clone(), &AbortLock::prepare());
+    assert!(!is_solved);
+    assert!(reduced.remaining_cells() > 0);
+    assert!(reduced.conflicts().is_empty());
+  }
+
+  #[test]
+  fn restricted_technique_set() {
+    let data 
+      = "".to_string()
+      + "   " + "   " + "7  "
+      + "3  " + " 1 " + "   "
+      + "69 " + "  3" + "1  "
+
+      + "   " + "372" + " 5 "
+      + " 5 " + "8 9" + " 27"
+      + " 37" + "   " + " 89"
+
+      + "415" + "9  " + "   "
+      + "7 3" + "15 " + " 9 "
+      + "9  " + " 3 " + "  4";
+
+    let lock = AbortLock::prepare();
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    let pairs = TechniqueSet::singles().with(TechniqueKind::NakedPair).with(TechniqueKind::HiddenPair);
+    assert!(!solve_logically_using(sudoku.clone(), TechniqueSet::singles(), &lock).1);
+    assert!(solve_logically_using(sudoku.clone(), pairs, &lock).1);
+    assert!(solve_logically_using(sudoku.clone(), TechniqueSet::default(), &lock).1);
+
+    let (hardest, solved) = rate_difficulty_using(sudoku.clone(), pairs, &lock);
+    assert!(solved);
+    assert!(hardest.is_some_and(|kind| kind == TechniqueKind::NakedPair || kind == TechniqueKind::HiddenPair));
+    assert_eq!((None, false), rate_difficulty_using(sudoku, TechniqueSet::none(), &lock));
+    assert!(!TechniqueSet::all().without(TechniqueKind::XWing).contains(TechniqueKind::XWing));
+  }
+
+  #[test]
+  fn stalled_techniques_terminate() {
+    let data 
+      = "".to_string()
+      + "8  " + "   " + "   "
+      + "  3" + "6  " + "   "
+      + " 7 " + "  9" + "2  "
+
+      + " 5 " + "  7" + "   "
+      + "   " + " 45" + "7  "
+      + "   " + "1  " + " 3 "
+
+      + "  1" + "   " + " 68"
+      + "  8" + "5  " + " 1 "
+      + " 9 " + "   " + "4  ";
+
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    let (histogram, is_solved) = difficulty_histogram(sudoku.clone(), &AbortLock::prepare());
+    assert!(!is_solved);
+    let candidates: usize = sudoku.iter().map(|(_, pos)| sudoku.available(pos).size() as usize).sum();
+    assert!(histogram.values().sum::<usize>() <= candidates);
+
+    let mut board = CandidateBoard::new(sudoku.clone());
+    let fingerprint = progress_fingerprint(&board);
+    assert_eq!(fingerprint, progress_fingerprint(&CandidateBoard::new(sudoku)));
+    let digit = board.candidates((1, 0)).to_vec()[0];
+    assert!(board.eliminate((1, 0), digit));
+    let reduced = progress_fingerprint(&board);
+    assert_ne!(fingerprint, reduced);
+    assert!(!board.eliminate((1, 0), digit));
+    assert_eq!(reduced, progress_fingerprint(&board));
+  }
+
+  #[test]
+  fn xy_wing_elimination() {
+    let data 
+      = "".to_string()
+      + "5  " + " 2 " + " 6 "
+      + " 96" + "   " + "  3"
+      + "3 4" + "5 7" + "   "
+
+      + "9  " + "  2" + "68 "
+      + " 8 " + " 16" + "  5"
+      + "6  " + "   " + "   "
+
+      + "8  " + "2  " + "3  "
+      + "1 5" + "  8" + "   "
+      + "   " + "  1" + "   ";
+
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    let expected = vec![((1, 3), 1), ((1, 5), 1), ((2, 0), 1), ((2, 0), 7)];
+    assert!(naked_pairs(&sudoku).is_empty());
+    assert!(pointing_pairs(&sudoku).is_empty());
+    assert!(x_wing(&sudoku).is_empty());
+    assert_eq!(expected, xy_wing(&sudoku).into_iter().sorted().collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn w_wing_elimination() {
+    let data 
+      = "".to_string()
+      + "3 4" + " 8 " + "   "
+      + " 7 " + " 6 " + "   "
+      + "1 5" + " 34" + " 7 "
+
+      + " 28" + "1  " + "  9"
+      + " 4 " + " 7 " + "5  "
+      + "6  " + "4 3" + "   "
+
+      + "   " + "   " + "2  "
+      + "   " + "   " + "3  "
+      + "4 9" + "8  " + " 65";
+
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    assert!(xy_wing(&sudoku).is_empty());
+    assert!(sudoku.available((6, 5)).contains(1));
+    assert_eq!(vec![((6, 5), 1)], w_wing(&sudoku));
+  }
+
+  #[test]
+  fn simple_coloring_elimination() {
+    let data 
+      = "".to_string()
+      + "864" + "7  " + "295"
+      + "137" + "592" + "   "
+      + "295" + "   " + "137"
+
+      + "4  " + "  7" + " 23"
+      + "7  " + "32 " + "  9"
+      + " 23" + "   " + "71 "
+
+      + " 42" + " 76" + "3 1"
+      + "3  " + "24 " + " 7 "
+      + "678" + "1  " + "942";
+
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    let expected = vec![
+      ((0, 5), 9), ((1, 4), 5), ((2, 7), 9), ((3, 3), 9), ((3, 6), 9), ((5, 5), 9), ((5, 7), 5)
+    ];
+    assert!(x_wing(&sudoku).is_empty());
+    assert!(xy_wing(&sudoku).is_empty());
+    assert!(w_wing(&sudoku).is_empty());
+    assert_eq!(vec![((5, 7), 5)], naked_pairs(&sudoku));
+    assert_eq!(expected, simple_coloring(&sudoku).into_iter().sorted().collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn hidden_pair_elimination() {
+    let data 
+      = "".to_string()
+      + "   " + "   " + "   "
+      + "   " + "1  " + " 2 "
+      + "   " + " 2 " + "1  "
+
+      + "   " + "   " + "   "
+      + "  1" + "   " + "   "
+      + "   " + "   " + "   "
+
+      + "   " + "   " + "   "
+      + "  2" + "   " + "   "
+      + "   " + "   " + "   ";
+
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    let expected: Vec<Elimination> = vec![(0, 0), (1, 0)].into_iter()
+      .flat_map(|pos| (3 ..= 9).map(move |digit| (pos, digit)))
+      .collect();
+    assert_eq!(expected, hidden_pairs(&sudoku).into_iter().sorted().collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn claiming_elimination() {
+    let data 
+      = "".to_string()
+      + "   " + "234" + "567"
+      + "   " + "   " + "   "
+      + "   " + "   " + "   "
+
+      + "   " + "   " + "   "
+      + "   " + "   " + "   "
+      + "   " + "   " + "   "
+
+      + "   " + "   " + "   "
+      + "   " + "   " + "   "
+      + "   " + "   " + "   ";
+
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    let expected: Vec<Elimination> = (0 .. 3)
+      .flat_map(|x| (1 .. 3).map(move |y| (x, y)))
+      .flat_map(|pos| vec![1, 8, 9].into_iter().map(move |digit| (pos, digit)))
+      .sorted()
+      .collect();
+    assert!(pointing_pairs(&sudoku).is_empty());
+    assert_eq!(expected, claiming(&sudoku).into_iter().sorted().collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn swordfish_on_rows() {
+    let data 
+      = "".to_string()
+      + " 23" + "4 5" + "67 "
+      + "   " + "   " + "   "
+      + "   " + "   " + "   "
+
+      + "   " + "   " + "   "
+      + " 56" + "7 8" + "92 "
+      + "   " + "   " + "   "
+
+      + "   " + "   " + "   "
+      + "   " + "   " + "   "
+      + " 89" + "2 3" + "45 ";
+
+    let sudoku = Sudoku::load(data.as_str(), 3);
+    let expected: Vec<Elimination> = vec![0, 4, 8].into_iter()
+      .flat_map(|x| vec![1, 2, 3, 5, 6, 7].into_iter().map(move |y| ((x, y), 1)))
+      .sorted()
+      .collect();
+
+    assert!(x_wing(&sudoku).is_empty());
+    assert_eq!(expected, swordfish(&sudoku).into_iter().sorted().collect::<Vec<_>>());
+  }
+}
@@ -4,6 +4,10 @@
 
 extern crate wasm_bindgen_test;
 use wasm_bindgen_test::*;
+use wasm_sudoku_solver::generate_puzzle;
+use wasm_sudoku_solver::sudoku::Sudoku;
+use wasm_sudoku_solver::solver::has_unique_solution;
+use wasm_sudoku_solver::abort_lock::AbortLock;
 
 wasm_bindgen_test_configure!(run_in_browser);
 
@@ -11,3 +15,15 @@ wasm_bindgen_test_configure!(run_in_browser);
 fn pass() {
     assert_eq!(1 + 1, 2);
 }
+
+#[wasm_bindgen_test]
+fn generated_puzzle_is_unique() {
+    let lock = AbortLock::prepare();
+    let puzzle = generate_puzzle(3, 1, 2024, &lock).unwrap();
+    let sudoku = Sudoku::load_checked(puzzle.as_str(), 3).unwrap();
+    assert!(has_unique_solution(&sudoku, &lock));
+    assert_eq!(Some(puzzle), generate_puzzle(3, 1, 2024, &lock));
+    assert_eq!(None, generate_puzzle(0, 1, 2024, &lock));
+    assert_eq!(None, generate_puzzle(6, 1, 2024, &lock));
+    assert_eq!(None, generate_puzzle(3, 1, 2024, &AbortLock::aborted()));
+}